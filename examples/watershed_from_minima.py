@@ -3,9 +3,7 @@ import scipy.ndimage as ndi
 import napari
 
 from skimage import data, filters, segmentation
-from edt import edt
-
-from pyift import watershed_from_minima
+from pyift import euclidean_distance_transform, relabel_sequential, watershed_from_minima
 
 
 def main() -> None:
@@ -18,7 +16,7 @@ def main() -> None:
     print(blurred.shape)
 
     foreground = blurred > filters.threshold_otsu(blurred)
-    dist = edt(foreground, parallel=4)
+    dist = euclidean_distance_transform(foreground)
     dist = (dist.max() - dist).astype("uint8")
 
     start = time.time()
@@ -28,7 +26,7 @@ def main() -> None:
     start = time.time()
     labels = watershed_from_minima(dist, mask=foreground, h=5)
     print("IFT watershed time: ", time.time() - start)
-    labels, _, _ = segmentation.relabel_sequential(labels)
+    labels, _, _ = relabel_sequential(labels, dtype="uint16")
 
     viewer = napari.Viewer()
     viewer.add_image(nuclei)
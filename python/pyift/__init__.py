@@ -1 +1,31 @@
-from pyift.watershed import watershed_from_minima
+from pyift.component_tree import ComponentTree
+from pyift.distance import euclidean_distance_transform, geodesic_distance_transform
+from pyift.forest import OptimumPathForest
+from pyift.graph import graph_foresting_transform
+from pyift.hierarchy import WatershedHierarchy, hierarchical_watershed
+from pyift.labels import relabel_sequential
+from pyift.minimal_path import minimal_path
+from pyift.morphology import (
+    area_closing,
+    area_opening,
+    extended_maxima,
+    extended_minima,
+    h_maxima,
+    h_minima,
+    reconstruct,
+    regional_maxima,
+    regional_minima,
+    volume_closing,
+    volume_opening,
+)
+from pyift.opf import OPFClassifier, opf_clustering
+from pyift.rag import RegionAdjacencyGraph, region_adjacency_graph
+from pyift.superpixels import disf_superpixels, isf_superpixels
+from pyift.watershed import (
+    DifferentialWatershed,
+    arc_weight_watershed,
+    dynamic_tree_watershed,
+    oriented_watershed,
+    watershed_from_markers,
+    watershed_from_minima,
+)
@@ -0,0 +1,132 @@
+import numpy as np
+from numpy.typing import ArrayLike
+from pyift import _rust_ift
+
+COMPONENT_TREE = {
+    "uint8": _rust_ift.ComponentTreeU8,
+    "uint16": _rust_ift.ComponentTreeU16,
+    "uint32": _rust_ift.ComponentTreeU32,
+    "int16": _rust_ift.ComponentTreeI16,
+    "int32": _rust_ift.ComponentTreeI32,
+    "int64": _rust_ift.ComponentTreeI64,
+    "float32": _rust_ift.ComponentTreeF32,
+    "float64": _rust_ift.ComponentTreeF64,
+}
+
+ATTRIBUTES = ("area", "volume", "height")
+
+
+class ComponentTree:
+    """
+    Max-tree or min-tree of an image, the tree of the connected components of its upper or lower level sets.
+
+    Nodes are ordered from the root (node 0), so the parent of a node always has a smaller index.
+
+    Parameters
+    ----------
+    image : ArrayLike
+        2 or 3-D grayscale image.
+    max_tree : bool
+        Build the max-tree (bright components) if `True`, otherwise the min-tree (dark components).
+    connectivity : int, optional
+        Number of neighbors, 4 or 8 in 2D and 6, 18 or 26 in 3D. If `None`, 4 (2D) or 6 (3D) is used.
+    """
+
+    def __init__(self, image: ArrayLike, max_tree: bool = True, connectivity: int | None = None) -> None:
+        image = np.asarray(image)
+
+        if image.ndim != 2 and image.ndim != 3:
+            raise ValueError(f"Unsupported dimension {image.ndim}, must be 2 or 3.")
+
+        if connectivity is None:
+            connectivity = 2 * image.ndim
+
+        valid_connectivity = (4, 8) if image.ndim == 2 else (6, 18, 26)
+        if connectivity not in valid_connectivity:
+            raise ValueError(f"Connectivity must be one of {valid_connectivity}. Found {connectivity} instead.")
+
+        tree_cls = COMPONENT_TREE.get(str(image.dtype))
+        if tree_cls is None:
+            raise ValueError(f"Unsupported dtype {image.dtype}.")
+
+        if np.issubdtype(image.dtype, np.floating) and np.isnan(image).any():
+            raise ValueError("Image must not contain NaN.")
+
+        self._tree = tree_cls(image, max_tree, connectivity)
+
+    @property
+    def parents(self) -> np.ndarray:
+        """(N,) parent of each node, the root is its own parent."""
+        return self._tree.parents()
+
+    @property
+    def levels(self) -> np.ndarray:
+        """(N,) gray level of each node."""
+        return self._tree.levels()
+
+    @property
+    def node_map(self) -> np.ndarray:
+        """Node of each pixel, same shape as the image."""
+        return self._tree.node_map()
+
+    @property
+    def area(self) -> np.ndarray:
+        """(N,) number of pixels of each node."""
+        return self._tree.area()
+
+    @property
+    def volume(self) -> np.ndarray:
+        """(N,) sum of the distances of the node pixels to the parent level."""
+        return self._tree.volume()
+
+    @property
+    def height(self) -> np.ndarray:
+        """(N,) distance between the extremal level of the node and the parent level."""
+        return self._tree.height()
+
+    @property
+    def bbox(self) -> np.ndarray:
+        """(N, 2 * ndim) minimum coordinates followed by the maximum coordinates of each node."""
+        return self._tree.bbox()
+
+    def reconstruct(self, keep: ArrayLike) -> np.ndarray:
+        """
+        Reconstruct an image from the kept nodes, pixels of removed nodes get the level of their closest kept ancestor.
+
+        Parameters
+        ----------
+        keep : ArrayLike
+            (N,) boolean array of the nodes to keep, the root is always kept.
+
+        Returns
+        -------
+        np.ndarray
+            Filtered image.
+        """
+        keep = np.ascontiguousarray(keep, dtype=bool)
+        if keep.shape != self.parents.shape:
+            raise ValueError(f"`keep` must have shape {self.parents.shape}. Found {keep.shape} instead.")
+        return self._tree.reconstruct(keep)
+
+    def filter(self, attribute: str | ArrayLike, threshold: float) -> np.ndarray:
+        """
+        Remove the nodes whose attribute is below `threshold` and reconstruct the image.
+
+        Parameters
+        ----------
+        attribute : str | ArrayLike
+            One of "area", "volume" or "height", or a (N,) array of node attributes.
+        threshold : float
+            Nodes with attribute greater or equal to `threshold` are kept.
+
+        Returns
+        -------
+        np.ndarray
+            Filtered image.
+        """
+        if isinstance(attribute, str):
+            if attribute not in ATTRIBUTES:
+                raise ValueError(f"Unknown attribute {attribute}, must be one of {ATTRIBUTES}.")
+            attribute = getattr(self, attribute)
+
+        return self.reconstruct(np.asarray(attribute) >= threshold)
@@ -0,0 +1,138 @@
+from typing import Sequence
+
+import numpy as np
+from numpy.typing import ArrayLike
+from pyift import _rust_ift
+
+EDT = {
+    2: _rust_ift.euclidean_distance_transform_2d,
+    3: _rust_ift.euclidean_distance_transform_3d,
+}
+
+GDT = {
+    2: _rust_ift.geodesic_distance_transform_2d,
+    3: _rust_ift.geodesic_distance_transform_3d,
+}
+
+
+def euclidean_distance_transform(
+    image: ArrayLike,
+    spacing: Sequence[float] | None = None,
+    return_features: bool = False,
+) -> ArrayLike | tuple[ArrayLike, ArrayLike]:
+    """
+    Compute the exact Euclidean distance transform of a binary image, using the IFT for isotropic spacing.
+
+    Parameters
+    ----------
+    image : ArrayLike
+        2 or 3-D binary image, distances are computed from nonzero pixels to the closest zero pixel.
+    spacing : Sequence[float], optional
+        Pixel spacing along each axis. If `None`, isotropic unit spacing is used.
+    return_features : bool
+        If `True`, also returns the flat index of the closest zero pixel (feature transform).
+
+    Returns
+    -------
+    ArrayLike or tuple[ArrayLike, ArrayLike]
+        Distance map and, optionally, the feature transform.
+    """
+    image = np.asarray(image, dtype=bool)
+
+    edt_func = EDT.get(image.ndim)
+    if edt_func is None:
+        raise ValueError(f"Unsupported dimension {image.ndim}, must be 2 or 3.")
+
+    if spacing is None:
+        spacing = [1.0] * image.ndim
+
+    if len(spacing) != image.ndim:
+        raise ValueError(f"Spacing must have length {image.ndim}. Found {len(spacing)} instead.")
+
+    if any(s <= 0 for s in spacing):
+        raise ValueError(f"Spacing must be positive. Found {spacing}.")
+
+    distances, features = edt_func(image, [float(s) for s in spacing])
+
+    if return_features:
+        return distances, features
+
+    return distances
+
+
+def geodesic_distance_transform(
+    markers: ArrayLike,
+    mask: ArrayLike | None = None,
+    spacing: Sequence[float] | None = None,
+    connectivity: int | None = None,
+    intensity: ArrayLike | None = None,
+    intensity_weight: float = 1.0,
+) -> tuple[ArrayLike, ArrayLike]:
+    """
+    Compute the geodesic distance from markers to every pixel, paths are restricted to the mask.
+
+    Parameters
+    ----------
+    markers : ArrayLike
+        2 or 3-D integer label image, nonzero pixels are used as seeds.
+    mask : ArrayLike, optional
+        Binary mask of the same shape as `markers`. If `None`, the whole image is considered.
+    spacing : Sequence[float], optional
+        Pixel spacing along each axis. If `None`, arcs between direct neighbors have unit length.
+    connectivity : int, optional
+        Number of neighbors of each pixel (4 or 8 in 2D, 6, 18 or 26 in 3D). If `None`, uses all of them.
+    intensity : ArrayLike, optional
+        Grayscale image of the same shape as `markers`, if given arcs are also weighted by its variation,
+        w(p, q) = sqrt(|p - q|^2 + (intensity_weight * (I(q) - I(p)))^2).
+    intensity_weight : float
+        Weight of the intensity variation on the arc weights.
+
+    Returns
+    -------
+    tuple[ArrayLike, ArrayLike]
+        Geodesic distances (infinite when not reachable) and label of the closest marker.
+    """
+    markers = np.asarray(markers)
+
+    gdt_func = GDT.get(markers.ndim)
+    if gdt_func is None:
+        raise ValueError(f"Unsupported dimension {markers.ndim}, must be 2 or 3.")
+
+    if mask is None:
+        mask = np.ones_like(markers, dtype=bool)
+    mask = np.asarray(mask, dtype=bool)
+
+    if mask.shape != markers.shape:
+        raise ValueError(
+            f"Mask and markers must have the same shape. Found {mask.shape} and {markers.shape} instead."
+        )
+
+    if np.any(markers < 0):
+        raise ValueError("Markers must be non-negative.")
+
+    if spacing is None:
+        spacing = [1.0] * markers.ndim
+
+    if len(spacing) != markers.ndim:
+        raise ValueError(f"Spacing must have length {markers.ndim}. Found {len(spacing)} instead.")
+
+    if any(s <= 0 for s in spacing):
+        raise ValueError(f"Spacing must be positive. Found {spacing}.")
+
+    if connectivity is None:
+        connectivity = 3**markers.ndim - 1
+
+    valid_connectivity = (4, 8) if markers.ndim == 2 else (6, 18, 26)
+    if connectivity not in valid_connectivity:
+        raise ValueError(f"Connectivity must be one of {valid_connectivity}. Found {connectivity} instead.")
+
+    if intensity is not None:
+        intensity = np.asarray(intensity, dtype=np.float64)
+        if intensity.shape != markers.shape:
+            raise ValueError(
+                f"Intensity and markers must have the same shape. Found {intensity.shape} and {markers.shape} instead."
+            )
+
+    markers = np.ascontiguousarray(markers, dtype=np.uintp)
+
+    return gdt_func(markers, mask, [float(s) for s in spacing], connectivity, intensity_weight, intensity)
@@ -0,0 +1,51 @@
+from dataclasses import dataclass
+
+import numpy as np
+from numpy.typing import ArrayLike
+
+
+@dataclass
+class OptimumPathForest:
+    """
+    Optimum-path forest computed by the image foresting transform.
+
+    Attributes
+    ----------
+    labels : ArrayLike
+        Label of each pixel, zero outside the mask.
+    costs : ArrayLike
+        Final path cost of each pixel.
+    preds : ArrayLike
+        Flat index of the predecessor of each pixel, -1 for roots and pixels outside the mask.
+    roots : ArrayLike
+        Flat index of the root of each pixel, -1 for pixels outside the mask.
+    """
+
+    labels: ArrayLike
+    costs: ArrayLike
+    preds: ArrayLike
+    roots: ArrayLike
+
+    def path(self, index: int | tuple[int, ...]) -> ArrayLike:
+        """
+        Optimum path from the root of `index` up to `index`.
+
+        Parameters
+        ----------
+        index : int or tuple of int
+            Flat index or coordinates of the path end point.
+
+        Returns
+        -------
+        ArrayLike
+            (N, ndim) array of the path coordinates, starting at its root.
+        """
+        if isinstance(index, tuple):
+            index = np.ravel_multi_index(index, self.preds.shape)
+
+        preds = self.preds.ravel()
+        path = [index]
+        while preds[path[-1]] >= 0:
+            path.append(preds[path[-1]])
+
+        return np.stack(np.unravel_index(path[::-1], self.preds.shape), axis=-1)
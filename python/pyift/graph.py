@@ -0,0 +1,77 @@
+from typing import Any
+
+import numpy as np
+from numpy.typing import ArrayLike
+from pyift import _rust_ift
+from pyift.forest import OptimumPathForest
+
+
+def graph_foresting_transform(
+    graph: Any,
+    seeds: ArrayLike,
+    labels: ArrayLike | None = None,
+    path_cost: str = "max",
+) -> OptimumPathForest:
+    """
+    Compute the image foresting transform on a weighted graph in compressed sparse row (CSR) format.
+
+    The arc (p, q) exists when q is in `indices[indptr[p]:indptr[p + 1]]` and its weight is stored at the same
+    position of `data`, undirected graphs must store both arcs.
+
+    Parameters
+    ----------
+    graph : Any
+        scipy.sparse matrix (or any object with a `tocsr` method) or (indptr, indices, data) tuple of arrays.
+    seeds : ArrayLike
+        Indices of the seed nodes, they have cost zero.
+    labels : ArrayLike, optional
+        Label of each seed, defaults to 1, 2, ..., len(seeds).
+    path_cost : str
+        "max" for the maximum arc weight along the path or "sum" for the sum of the non-negative arc weights.
+
+    Returns
+    -------
+    OptimumPathForest
+        Labels, costs, predecessors and roots of each node, predecessors and roots are node indices.
+    """
+    if hasattr(graph, "tocsr"):
+        csr = graph.tocsr()
+        if csr.shape[0] != csr.shape[1]:
+            raise ValueError(f"Graph matrix must be square. Found shape {csr.shape}.")
+        graph = (csr.indptr, csr.indices, csr.data)
+
+    indptr, indices, data = graph
+    indptr = np.ascontiguousarray(indptr, dtype=np.uintp)
+    indices = np.ascontiguousarray(indices, dtype=np.uintp)
+    data = np.ascontiguousarray(data, dtype=np.float64)
+
+    n_nodes = len(indptr) - 1
+    if n_nodes < 0 or indptr[0] != 0 or np.any(np.diff(indptr.astype(np.int64)) < 0):
+        raise ValueError("`indptr` must start at zero and be non-decreasing.")
+
+    if len(indices) != indptr[-1] or len(data) != indptr[-1]:
+        raise ValueError(f"`indices` and `data` must have {indptr[-1]} elements.")
+
+    if len(indices) > 0 and indices.max() >= n_nodes:
+        raise ValueError(f"`indices` must be smaller than the number of nodes {n_nodes}.")
+
+    seeds = np.ascontiguousarray(seeds, dtype=np.uintp).ravel()
+    if len(seeds) > 0 and seeds.max() >= n_nodes:
+        raise ValueError(f"`seeds` must be smaller than the number of nodes {n_nodes}.")
+
+    if labels is None:
+        labels = np.arange(1, len(seeds) + 1)
+    labels = np.ascontiguousarray(labels, dtype=np.uintp).ravel()
+
+    if len(labels) != len(seeds):
+        raise ValueError(f"`labels` must have one label per seed. Found {len(labels)} and {len(seeds)}.")
+
+    if path_cost not in ("max", "sum"):
+        raise ValueError(f"Unknown path cost {path_cost}, must be 'max' or 'sum'.")
+
+    if path_cost == "sum" and np.any(data < 0):
+        raise ValueError("Arc weights must be non-negative for the 'sum' path cost.")
+
+    forest = _rust_ift.graph_foresting_transform(indptr, indices, data, seeds, labels, path_cost)
+
+    return OptimumPathForest(*forest)
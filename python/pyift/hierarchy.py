@@ -0,0 +1,118 @@
+from dataclasses import dataclass
+
+import numpy as np
+from numpy.typing import ArrayLike
+from pyift import _rust_ift
+
+HIERARCHICAL_WS = {
+    ("uint8", 2): _rust_ift.hierarchical_watershed_u8_2d,
+    ("uint8", 3): _rust_ift.hierarchical_watershed_u8_3d,
+    ("uint16", 2): _rust_ift.hierarchical_watershed_u16_2d,
+    ("uint16", 3): _rust_ift.hierarchical_watershed_u16_3d,
+    ("uint32", 2): _rust_ift.hierarchical_watershed_u32_2d,
+    ("uint32", 3): _rust_ift.hierarchical_watershed_u32_3d,
+    ("int16", 2): _rust_ift.hierarchical_watershed_i16_2d,
+    ("int16", 3): _rust_ift.hierarchical_watershed_i16_3d,
+    ("int32", 2): _rust_ift.hierarchical_watershed_i32_2d,
+    ("int32", 3): _rust_ift.hierarchical_watershed_i32_3d,
+    ("int64", 2): _rust_ift.hierarchical_watershed_i64_2d,
+    ("int64", 3): _rust_ift.hierarchical_watershed_i64_3d,
+    ("float32", 2): _rust_ift.hierarchical_watershed_f32_2d,
+    ("float32", 3): _rust_ift.hierarchical_watershed_f32_3d,
+    ("float64", 2): _rust_ift.hierarchical_watershed_f64_2d,
+    ("float64", 3): _rust_ift.hierarchical_watershed_f64_3d,
+}
+
+CUT_HIERARCHY = {
+    2: _rust_ift.cut_hierarchy_2d,
+    3: _rust_ift.cut_hierarchy_3d,
+}
+
+ATTRIBUTES = ("dynamics", "area", "volume")
+
+
+@dataclass
+class WatershedHierarchy:
+    """
+    Hierarchy of watershed segmentations.
+
+    Attributes
+    ----------
+    basins : ArrayLike
+        Catchment basins of the regional minima, labeled from 1.
+    parents : ArrayLike
+        Parent of each merge tree node, the leaves `basins - 1` come first and the root is its own parent.
+    altitudes : ArrayLike
+        Altitude of each merge tree node, zero for the leaves and increasing for the merges.
+    saliency : ArrayLike
+        Altitude at which the basins on each side of a boundary pixel are merged, zero inside basins.
+    """
+
+    basins: ArrayLike
+    parents: ArrayLike
+    altitudes: ArrayLike
+    saliency: ArrayLike
+
+    def cut(self, threshold: float | None = None, n_regions: int | None = None) -> ArrayLike:
+        """
+        Segmentation of the hierarchy at a given altitude or number of regions.
+
+        Parameters
+        ----------
+        threshold : float, optional
+            Merges with altitude up to `threshold` are applied.
+        n_regions : int, optional
+            Number of regions of the segmentation, it is clipped to the number of basins.
+
+        Returns
+        -------
+        ArrayLike
+            Labels of the regions starting from 1.
+        """
+        if (threshold is None) == (n_regions is None):
+            raise ValueError("Exactly one of `threshold` or `n_regions` must be provided.")
+
+        n_basins = (len(self.parents) + 1) // 2
+        if threshold is not None:
+            n_merges = np.searchsorted(self.altitudes[n_basins:], threshold, side="right")
+        else:
+            if n_regions < 1:
+                raise ValueError(f"`n_regions` must be positive. Found {n_regions}.")
+            n_merges = max(n_basins - n_regions, 0)
+
+        return CUT_HIERARCHY[self.basins.ndim](self.basins, self.parents, int(n_merges))
+
+
+def hierarchical_watershed(topology: ArrayLike, attribute: str = "dynamics") -> WatershedHierarchy:
+    """
+    Compute the hierarchical watershed of an image, where the basins of its regional minima are merged in the order
+    of their extinction values for the given attribute.
+
+    Parameters
+    ----------
+    topology : ArrayLike
+        2 or 3-D grayscale image.
+    attribute : str
+        Merging order, "dynamics" (depth), "area" or "volume" of the basins.
+
+    Returns
+    -------
+    WatershedHierarchy
+        Basins, merge tree and saliency map of the hierarchy.
+    """
+    topology = np.asarray(topology)
+
+    if attribute not in ATTRIBUTES:
+        raise ValueError(f"Unknown attribute {attribute}, must be one of {ATTRIBUTES}.")
+
+    ws_func = HIERARCHICAL_WS.get((str(topology.dtype), topology.ndim))
+    if ws_func is None:
+        if topology.ndim != 2 and topology.ndim != 3:
+            raise ValueError(f"Unsupported dimension {topology.ndim}, must be 2 or 3.")
+        else:
+            raise ValueError(f"Unsupported dtype {topology.dtype}.")
+
+    if np.issubdtype(topology.dtype, np.floating) and np.isnan(topology).any():
+        raise ValueError("Topology must not contain NaN.")
+
+    return WatershedHierarchy(*ws_func(topology, attribute))
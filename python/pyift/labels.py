@@ -0,0 +1,58 @@
+import numpy as np
+from numpy.typing import ArrayLike, DTypeLike
+from pyift import _rust_ift
+
+RELABEL_SEQUENTIAL = {
+    ("uint16", 2): _rust_ift.relabel_sequential_u16_2d,
+    ("uint16", 3): _rust_ift.relabel_sequential_u16_3d,
+    ("uint32", 2): _rust_ift.relabel_sequential_u32_2d,
+    ("uint32", 3): _rust_ift.relabel_sequential_u32_3d,
+    ("uint64", 2): _rust_ift.relabel_sequential_u64_2d,
+    ("uint64", 3): _rust_ift.relabel_sequential_u64_3d,
+}
+
+
+def relabel_sequential(
+    labels: ArrayLike,
+    dtype: DTypeLike = np.uint32,
+) -> tuple[ArrayLike, dict[int, int], ArrayLike]:
+    """
+    Relabel a label image to 1, 2, ..., N keeping the order of the original labels, 0 stays background.
+
+    Parameters
+    ----------
+    labels : ArrayLike
+        2 or 3-D non-negative integer label image, e.g. the output of `watershed_from_minima`.
+    dtype : DTypeLike
+        Output type, uint16, uint32 or uint64.
+
+    Returns
+    -------
+    ArrayLike
+        Relabeled image of type `dtype`.
+    dict[int, int]
+        Forward map from the original labels to the new ones.
+    ArrayLike
+        (N + 1,) inverse map, `inverse[new]` is the original label of `new` and `inverse[0]` is 0.
+    """
+    labels = np.asarray(labels)
+    dtype = np.dtype(dtype)
+
+    relabel_func = RELABEL_SEQUENTIAL.get((str(dtype), labels.ndim))
+    if relabel_func is None:
+        if labels.ndim != 2 and labels.ndim != 3:
+            raise ValueError(f"Unsupported dimension {labels.ndim}, must be 2 or 3.")
+        else:
+            raise ValueError(f"Unsupported output dtype {dtype}, must be uint16, uint32 or uint64.")
+
+    if not np.issubdtype(labels.dtype, np.integer):
+        raise ValueError(f"Labels must be integers. Found {labels.dtype}.")
+
+    if labels.size > 0 and labels.min() < 0:
+        raise ValueError("Labels must be non-negative.")
+
+    labels = np.ascontiguousarray(labels, dtype=np.uintp)
+    relabeled, inverse = relabel_func(labels)
+    forward = {int(old): new for new, old in enumerate(inverse)}
+
+    return relabeled, forward, inverse
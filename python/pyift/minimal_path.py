@@ -0,0 +1,51 @@
+from typing import Sequence
+
+import numpy as np
+from numpy.typing import ArrayLike
+from pyift import _rust_ift
+
+
+def minimal_path(
+    cost: ArrayLike,
+    start: Sequence[int],
+    end: Sequence[int],
+    method: str = "livewire",
+) -> ArrayLike:
+    """
+    Compute the optimum path between two pixels of a 2-D cost image using the IFT with 8-connectivity.
+
+    The weight of the arc (p, q) is the cost of `q` scaled by the arc length, and the propagation stops as soon as
+    the optimum path to `end` is found.
+
+    Parameters
+    ----------
+    cost : ArrayLike
+        2-D non-negative cost image, paths follow its low values (e.g. an inverted gradient).
+    start : Sequence[int]
+        (y, x) coordinates of the first pixel of the path.
+    end : Sequence[int]
+        (y, x) coordinates of the last pixel of the path.
+    method : str
+        Path cost, "livewire" for the sum of the arc weights or "riverbed" for the weight of the last arc.
+
+    Returns
+    -------
+    ArrayLike
+        (N, 2) array of the path coordinates ordered from `start` to `end`, empty if `end` is not reachable.
+    """
+    cost = np.asarray(cost, dtype=np.float64)
+
+    if cost.ndim != 2:
+        raise ValueError(f"Unsupported dimension {cost.ndim}, must be 2.")
+
+    if method not in ("livewire", "riverbed"):
+        raise ValueError(f"Unknown method {method}, must be 'livewire' or 'riverbed'.")
+
+    start = [int(c) for c in start]
+    end = [int(c) for c in end]
+
+    for point in (start, end):
+        if len(point) != 2 or not (0 <= point[0] < cost.shape[0] and 0 <= point[1] < cost.shape[1]):
+            raise ValueError(f"Point {point} is outside the image of shape {cost.shape}.")
+
+    return _rust_ift.minimal_path_2d(cost, start, end, method == "riverbed")
@@ -0,0 +1,389 @@
+from typing import Callable
+
+import numpy as np
+from numpy.typing import ArrayLike
+from pyift import _rust_ift
+
+RECONSTRUCTION = {
+    ("uint8", 2): _rust_ift.reconstruction_u8_2d,
+    ("uint8", 3): _rust_ift.reconstruction_u8_3d,
+    ("uint16", 2): _rust_ift.reconstruction_u16_2d,
+    ("uint16", 3): _rust_ift.reconstruction_u16_3d,
+    ("uint32", 2): _rust_ift.reconstruction_u32_2d,
+    ("uint32", 3): _rust_ift.reconstruction_u32_3d,
+    ("int16", 2): _rust_ift.reconstruction_i16_2d,
+    ("int16", 3): _rust_ift.reconstruction_i16_3d,
+    ("int32", 2): _rust_ift.reconstruction_i32_2d,
+    ("int32", 3): _rust_ift.reconstruction_i32_3d,
+    ("int64", 2): _rust_ift.reconstruction_i64_2d,
+    ("int64", 3): _rust_ift.reconstruction_i64_3d,
+    ("float32", 2): _rust_ift.reconstruction_f32_2d,
+    ("float32", 3): _rust_ift.reconstruction_f32_3d,
+    ("float64", 2): _rust_ift.reconstruction_f64_2d,
+    ("float64", 3): _rust_ift.reconstruction_f64_3d,
+}
+
+
+REGIONAL_EXTREMA = {
+    ("uint8", 2): _rust_ift.regional_extrema_u8_2d,
+    ("uint8", 3): _rust_ift.regional_extrema_u8_3d,
+    ("uint16", 2): _rust_ift.regional_extrema_u16_2d,
+    ("uint16", 3): _rust_ift.regional_extrema_u16_3d,
+    ("uint32", 2): _rust_ift.regional_extrema_u32_2d,
+    ("uint32", 3): _rust_ift.regional_extrema_u32_3d,
+    ("int16", 2): _rust_ift.regional_extrema_i16_2d,
+    ("int16", 3): _rust_ift.regional_extrema_i16_3d,
+    ("int32", 2): _rust_ift.regional_extrema_i32_2d,
+    ("int32", 3): _rust_ift.regional_extrema_i32_3d,
+    ("int64", 2): _rust_ift.regional_extrema_i64_2d,
+    ("int64", 3): _rust_ift.regional_extrema_i64_3d,
+    ("float32", 2): _rust_ift.regional_extrema_f32_2d,
+    ("float32", 3): _rust_ift.regional_extrema_f32_3d,
+    ("float64", 2): _rust_ift.regional_extrema_f64_2d,
+    ("float64", 3): _rust_ift.regional_extrema_f64_3d,
+}
+
+H_EXTREMA = {
+    ("uint8", 2): _rust_ift.h_extrema_u8_2d,
+    ("uint8", 3): _rust_ift.h_extrema_u8_3d,
+    ("uint16", 2): _rust_ift.h_extrema_u16_2d,
+    ("uint16", 3): _rust_ift.h_extrema_u16_3d,
+    ("uint32", 2): _rust_ift.h_extrema_u32_2d,
+    ("uint32", 3): _rust_ift.h_extrema_u32_3d,
+    ("int16", 2): _rust_ift.h_extrema_i16_2d,
+    ("int16", 3): _rust_ift.h_extrema_i16_3d,
+    ("int32", 2): _rust_ift.h_extrema_i32_2d,
+    ("int32", 3): _rust_ift.h_extrema_i32_3d,
+    ("int64", 2): _rust_ift.h_extrema_i64_2d,
+    ("int64", 3): _rust_ift.h_extrema_i64_3d,
+    ("float32", 2): _rust_ift.h_extrema_f32_2d,
+    ("float32", 3): _rust_ift.h_extrema_f32_3d,
+    ("float64", 2): _rust_ift.h_extrema_f64_2d,
+    ("float64", 3): _rust_ift.h_extrema_f64_3d,
+}
+
+AREA_FILTER = {
+    ("uint8", 2): _rust_ift.area_filter_u8_2d,
+    ("uint8", 3): _rust_ift.area_filter_u8_3d,
+    ("uint16", 2): _rust_ift.area_filter_u16_2d,
+    ("uint16", 3): _rust_ift.area_filter_u16_3d,
+    ("uint32", 2): _rust_ift.area_filter_u32_2d,
+    ("uint32", 3): _rust_ift.area_filter_u32_3d,
+    ("int16", 2): _rust_ift.area_filter_i16_2d,
+    ("int16", 3): _rust_ift.area_filter_i16_3d,
+    ("int32", 2): _rust_ift.area_filter_i32_2d,
+    ("int32", 3): _rust_ift.area_filter_i32_3d,
+    ("int64", 2): _rust_ift.area_filter_i64_2d,
+    ("int64", 3): _rust_ift.area_filter_i64_3d,
+    ("float32", 2): _rust_ift.area_filter_f32_2d,
+    ("float32", 3): _rust_ift.area_filter_f32_3d,
+    ("float64", 2): _rust_ift.area_filter_f64_2d,
+    ("float64", 3): _rust_ift.area_filter_f64_3d,
+}
+
+VOLUME_FILTER = {
+    ("uint8", 2): _rust_ift.volume_filter_u8_2d,
+    ("uint8", 3): _rust_ift.volume_filter_u8_3d,
+    ("uint16", 2): _rust_ift.volume_filter_u16_2d,
+    ("uint16", 3): _rust_ift.volume_filter_u16_3d,
+    ("uint32", 2): _rust_ift.volume_filter_u32_2d,
+    ("uint32", 3): _rust_ift.volume_filter_u32_3d,
+    ("int16", 2): _rust_ift.volume_filter_i16_2d,
+    ("int16", 3): _rust_ift.volume_filter_i16_3d,
+    ("int32", 2): _rust_ift.volume_filter_i32_2d,
+    ("int32", 3): _rust_ift.volume_filter_i32_3d,
+    ("int64", 2): _rust_ift.volume_filter_i64_2d,
+    ("int64", 3): _rust_ift.volume_filter_i64_3d,
+    ("float32", 2): _rust_ift.volume_filter_f32_2d,
+    ("float32", 3): _rust_ift.volume_filter_f32_3d,
+    ("float64", 2): _rust_ift.volume_filter_f64_2d,
+    ("float64", 3): _rust_ift.volume_filter_f64_3d,
+}
+
+
+def reconstruct(
+    marker: ArrayLike,
+    mask_image: ArrayLike,
+    method: str = "dilation",
+) -> ArrayLike:
+    """
+    Compute the grayscale morphological reconstruction of a marker image using the IFT.
+
+    Parameters
+    ----------
+    marker : ArrayLike
+        2 or 3-D grayscale image, it is clipped to be below (dilation) or above (erosion) `mask_image`.
+    mask_image : ArrayLike
+        Grayscale image of the same shape and dtype as `marker` that constrains the reconstruction.
+    method : str
+        "dilation" to reconstruct `marker` under `mask_image` or "erosion" to reconstruct it over `mask_image`.
+
+    Returns
+    -------
+    ArrayLike
+        Reconstructed image.
+    """
+    marker = np.asarray(marker)
+    mask_image = np.asarray(mask_image)
+
+    if method not in ("dilation", "erosion"):
+        raise ValueError(f"Unknown method {method}, must be 'dilation' or 'erosion'.")
+
+    if marker.shape != mask_image.shape:
+        raise ValueError(
+            f"Marker and mask image must have the same shape. Found {marker.shape} and {mask_image.shape} instead."
+        )
+
+    if marker.dtype != mask_image.dtype:
+        raise ValueError(
+            f"Marker and mask image must have the same dtype. Found {marker.dtype} and {mask_image.dtype} instead."
+        )
+
+    rec_func = RECONSTRUCTION.get((str(marker.dtype), marker.ndim))
+    if rec_func is None:
+        if marker.ndim != 2 and marker.ndim != 3:
+            raise ValueError(f"Unsupported dimension {marker.ndim}, must be 2 or 3.")
+        else:
+            raise ValueError(f"Unsupported dtype {marker.dtype}.")
+
+    return rec_func(marker, mask_image, method == "dilation")
+
+
+def _dispatch(table: dict, image: np.ndarray) -> Callable:
+    func = table.get((str(image.dtype), image.ndim))
+    if func is None:
+        if image.ndim != 2 and image.ndim != 3:
+            raise ValueError(f"Unsupported dimension {image.ndim}, must be 2 or 3.")
+        else:
+            raise ValueError(f"Unsupported dtype {image.dtype}.")
+    return func
+
+
+def _h_transform(image: ArrayLike, h: float, maxima: bool) -> np.ndarray:
+    image = np.asarray(image)
+    if h < 0:
+        raise ValueError(f"`h` must be non-negative. Found {h}.")
+    return _dispatch(H_EXTREMA, image)(image, image.dtype.type(h), maxima)
+
+
+def regional_minima(image: ArrayLike) -> ArrayLike:
+    """
+    Label the regional minima, the connected plateaus without any lower neighbor.
+
+    Parameters
+    ----------
+    image : ArrayLike
+        2 or 3-D grayscale image.
+
+    Returns
+    -------
+    ArrayLike
+        Labels starting from 1 on the regional minima and 0 elsewhere.
+    """
+    image = np.asarray(image)
+    return _dispatch(REGIONAL_EXTREMA, image)(image, False)
+
+
+def regional_maxima(image: ArrayLike) -> ArrayLike:
+    """
+    Label the regional maxima, the connected plateaus without any higher neighbor.
+
+    Parameters
+    ----------
+    image : ArrayLike
+        2 or 3-D grayscale image.
+
+    Returns
+    -------
+    ArrayLike
+        Labels starting from 1 on the regional maxima and 0 elsewhere.
+    """
+    image = np.asarray(image)
+    return _dispatch(REGIONAL_EXTREMA, image)(image, True)
+
+
+def h_minima(image: ArrayLike, h: float) -> ArrayLike:
+    """
+    Compute the h-minima transform, the reconstruction by erosion of `image + h` over `image`.
+    Minima with depth up to `h` are filled.
+
+    Parameters
+    ----------
+    image : ArrayLike
+        2 or 3-D grayscale image.
+    h : float
+        Non-negative depth threshold, cast to the image dtype.
+
+    Returns
+    -------
+    ArrayLike
+        Transformed image with the same dtype as `image`.
+    """
+    return _h_transform(image, h, False)
+
+
+def h_maxima(image: ArrayLike, h: float) -> ArrayLike:
+    """
+    Compute the h-maxima transform, the reconstruction by dilation of `image - h` under `image`.
+    Maxima with height up to `h` are flattened.
+
+    Parameters
+    ----------
+    image : ArrayLike
+        2 or 3-D grayscale image.
+    h : float
+        Non-negative height threshold, cast to the image dtype.
+
+    Returns
+    -------
+    ArrayLike
+        Transformed image with the same dtype as `image`.
+    """
+    return _h_transform(image, h, True)
+
+
+def extended_minima(image: ArrayLike, h: float) -> ArrayLike:
+    """
+    Label the extended minima, the regional minima of the h-minima transform.
+    They can be edited and used as `markers` of `watershed_from_markers`.
+
+    Parameters
+    ----------
+    image : ArrayLike
+        2 or 3-D grayscale image.
+    h : float
+        Non-negative depth threshold, minima with depth up to `h` are ignored.
+
+    Returns
+    -------
+    ArrayLike
+        Labels starting from 1 on the extended minima and 0 elsewhere.
+    """
+    return regional_minima(h_minima(image, h))
+
+
+def extended_maxima(image: ArrayLike, h: float) -> ArrayLike:
+    """
+    Label the extended maxima, the regional maxima of the h-maxima transform.
+
+    Parameters
+    ----------
+    image : ArrayLike
+        2 or 3-D grayscale image.
+    h : float
+        Non-negative height threshold, maxima with height up to `h` are ignored.
+
+    Returns
+    -------
+    ArrayLike
+        Labels starting from 1 on the extended maxima and 0 elsewhere.
+    """
+    return regional_maxima(h_maxima(image, h))
+
+
+def _attribute_filter(
+    table: dict, image: ArrayLike, threshold: float, opening: bool, connectivity: int | None
+) -> np.ndarray:
+    image = np.asarray(image)
+    func = _dispatch(table, image)
+
+    if connectivity is None:
+        connectivity = 2 * image.ndim
+
+    valid_connectivity = (4, 8) if image.ndim == 2 else (6, 18, 26)
+    if connectivity not in valid_connectivity:
+        raise ValueError(f"Connectivity must be one of {valid_connectivity}. Found {connectivity} instead.")
+
+    if threshold < 0:
+        raise ValueError(f"Threshold must be non-negative. Found {threshold}.")
+
+    if np.issubdtype(image.dtype, np.floating) and np.isnan(image).any():
+        raise ValueError("Image must not contain NaN.")
+
+    return func(image, threshold, opening, connectivity)
+
+
+def area_opening(image: ArrayLike, area: int, connectivity: int | None = None) -> ArrayLike:
+    """
+    Remove the bright connected components with less than `area` pixels without blurring edges.
+
+    Parameters
+    ----------
+    image : ArrayLike
+        2 or 3-D grayscale image.
+    area : int
+        Minimum number of pixels of the kept components.
+    connectivity : int, optional
+        Number of neighbors, 4 or 8 in 2D and 6, 18 or 26 in 3D. If `None`, 4 (2D) or 6 (3D) is used.
+
+    Returns
+    -------
+    ArrayLike
+        Filtered image.
+    """
+    return _attribute_filter(AREA_FILTER, image, int(area), True, connectivity)
+
+
+def area_closing(image: ArrayLike, area: int, connectivity: int | None = None) -> ArrayLike:
+    """
+    Remove the dark connected components with less than `area` pixels without blurring edges.
+
+    Parameters
+    ----------
+    image : ArrayLike
+        2 or 3-D grayscale image.
+    area : int
+        Minimum number of pixels of the kept components.
+    connectivity : int, optional
+        Number of neighbors, 4 or 8 in 2D and 6, 18 or 26 in 3D. If `None`, 4 (2D) or 6 (3D) is used.
+
+    Returns
+    -------
+    ArrayLike
+        Filtered image.
+    """
+    return _attribute_filter(AREA_FILTER, image, int(area), False, connectivity)
+
+
+def volume_opening(image: ArrayLike, volume: float, connectivity: int | None = None) -> ArrayLike:
+    """
+    Remove the bright connected components with volume below `volume`.
+    The volume is the sum of the component intensities above the level where it merges with its surroundings.
+
+    Parameters
+    ----------
+    image : ArrayLike
+        2 or 3-D grayscale image.
+    volume : float
+        Minimum volume of the kept components.
+    connectivity : int, optional
+        Number of neighbors, 4 or 8 in 2D and 6, 18 or 26 in 3D. If `None`, 4 (2D) or 6 (3D) is used.
+
+    Returns
+    -------
+    ArrayLike
+        Filtered image.
+    """
+    return _attribute_filter(VOLUME_FILTER, image, float(volume), True, connectivity)
+
+
+def volume_closing(image: ArrayLike, volume: float, connectivity: int | None = None) -> ArrayLike:
+    """
+    Remove the dark connected components with volume below `volume`.
+    The volume is the sum of the component depths below the level where it merges with its surroundings.
+
+    Parameters
+    ----------
+    image : ArrayLike
+        2 or 3-D grayscale image.
+    volume : float
+        Minimum volume of the kept components.
+    connectivity : int, optional
+        Number of neighbors, 4 or 8 in 2D and 6, 18 or 26 in 3D. If `None`, 4 (2D) or 6 (3D) is used.
+
+    Returns
+    -------
+    ArrayLike
+        Filtered image.
+    """
+    return _attribute_filter(VOLUME_FILTER, image, float(volume), False, connectivity)
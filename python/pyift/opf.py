@@ -0,0 +1,144 @@
+import numpy as np
+from numpy.typing import ArrayLike
+from pyift import _rust_ift
+
+
+def _as_features(X: ArrayLike) -> np.ndarray:
+    X = np.asarray(X, dtype=np.float64)
+    if X.ndim != 2:
+        raise ValueError(f"Features must have shape (n_samples, n_features). Found {X.shape} instead.")
+    if np.isnan(X).any():
+        raise ValueError("Features must not contain NaN.")
+    return np.ascontiguousarray(X)
+
+
+class OPFClassifier:
+    """
+    Supervised optimum-path forest (OPF) classifier.
+
+    The training samples are nodes of a complete graph weighted by their Euclidean distances. The samples on the
+    minimum spanning tree edges between different classes are the prototypes, which conquer the remaining samples
+    with the fmax path cost. New samples get the class of the training sample offering them the optimum path.
+
+    Attributes
+    ----------
+    classes_ : np.ndarray
+        Class labels seen during `fit`.
+    """
+
+    def __init__(self) -> None:
+        self.classes_ = None
+        self._model = None
+
+    def fit(self, X: ArrayLike, y: ArrayLike) -> "OPFClassifier":
+        """
+        Fit the classifier on labeled samples.
+
+        Parameters
+        ----------
+        X : ArrayLike
+            (n_samples, n_features) training features.
+        y : ArrayLike
+            (n_samples,) class labels.
+
+        Returns
+        -------
+        OPFClassifier
+            The fitted classifier.
+        """
+        X = _as_features(X)
+        y = np.asarray(y)
+
+        if y.shape != (len(X),):
+            raise ValueError(f"Labels must have shape ({len(X)},). Found {y.shape} instead.")
+
+        if len(X) == 0:
+            raise ValueError("At least one training sample is required.")
+
+        self.classes_, indices = np.unique(y, return_inverse=True)
+        self._model = _rust_ift.OpfClassifierModel(X, np.ascontiguousarray(indices, dtype=np.uintp))
+
+        return self
+
+    def predict(self, X: ArrayLike) -> np.ndarray:
+        """
+        Classify samples by their optimum-path cost.
+
+        Parameters
+        ----------
+        X : ArrayLike
+            (n_samples, n_features) features.
+
+        Returns
+        -------
+        np.ndarray
+            (n_samples,) predicted class labels.
+        """
+        if self._model is None:
+            raise ValueError("The classifier must be fitted before calling `predict`.")
+
+        return self.classes_[self._model.predict(_as_features(X))]
+
+
+def opf_clustering(
+    X: ArrayLike,
+    k_max: int = 10,
+    channel_axis: int | None = None,
+    max_samples: int | None = 10_000,
+) -> tuple[np.ndarray, np.ndarray]:
+    """
+    Cluster samples with the unsupervised optimum-path forest (OPF).
+
+    The pdf of the samples is estimated with a Gaussian kernel on their k-nearest neighbors graph and each maximum
+    of the pdf roots a cluster, computed by the maximization IFT. The number of neighbors k in [1, `k_max`] is
+    the one minimizing the normalized cut of the resulting clusters.
+
+    The nearest neighbors are found by brute force, so time grows quadratically with the number of samples. Images
+    should be subsampled or reduced to superpixels before clustering, rather than passing every pixel.
+
+    Parameters
+    ----------
+    X : ArrayLike
+        (n_samples, n_features) features, or an image with the channels on `channel_axis`.
+    k_max : int
+        Maximum number of nearest neighbors.
+    channel_axis : int, optional
+        If provided, `X` is an image with the channels on this axis and each pixel is a sample.
+    max_samples : int, optional
+        Maximum number of samples, larger inputs are rejected to avoid the quadratic cost. If `None`, no limit.
+
+    Returns
+    -------
+    tuple[np.ndarray, np.ndarray]
+        Cluster labels starting from 1 and pdf of each sample, with the spatial shape of the image when
+        `channel_axis` is provided.
+    """
+    X = np.asarray(X, dtype=np.float64)
+
+    shape = None
+    if channel_axis is not None:
+        X = np.moveaxis(X, channel_axis, -1)
+        shape = X.shape[:-1]
+        X = X.reshape(-1, X.shape[-1])
+
+    X = _as_features(X)
+
+    if len(X) < 2:
+        raise ValueError(f"At least two samples are required. Found {len(X)}.")
+
+    if k_max < 1:
+        raise ValueError(f"`k_max` must be positive. Found {k_max}.")
+
+    if max_samples is not None and len(X) > max_samples:
+        raise ValueError(
+            f"Found {len(X)} samples, more than `max_samples` {max_samples}. "
+            "Subsample them or raise `max_samples`, the cost is quadratic in the number of samples."
+        )
+
+    labels, pdf, _ = _rust_ift.opf_clustering_samples(X, k_max)
+
+    if shape is not None:
+        labels = labels.reshape(shape)
+        pdf = pdf.reshape(shape)
+
+    return labels, pdf
@@ -0,0 +1,129 @@
+from dataclasses import dataclass
+from typing import Any
+
+import numpy as np
+from numpy.typing import ArrayLike
+from pyift import _rust_ift
+
+REGION_ADJACENCY_GRAPH = {
+    2: _rust_ift.region_adjacency_graph_2d,
+    3: _rust_ift.region_adjacency_graph_3d,
+}
+
+
+@dataclass
+class RegionAdjacencyGraph:
+    """
+    Region adjacency graph (RAG) of a label image, label 0 is background and has no node.
+
+    Nodes are sorted by label and edges by their node indices. The weight statistics are `None` when no weight
+    image is given, the weight of each pair of adjacent boundary pixels is the maximum of their weights.
+
+    Attributes
+    ----------
+    labels : ArrayLike
+        (n_nodes,) label of each node.
+    area : ArrayLike
+        (n_nodes,) number of pixels of each node.
+    centroids : ArrayLike
+        (n_nodes, ndim) mean coordinates of each node.
+    edges : ArrayLike
+        (n_edges, 2) node indices of each edge, the smaller one first.
+    boundary_length : ArrayLike
+        (n_edges,) number of pairs of adjacent pixels between the two regions of each edge.
+    node_weights : ArrayLike, optional
+        (n_nodes, 3) mean, minimum and maximum weight of the pixels of each node.
+    edge_weights : ArrayLike, optional
+        (n_edges, 3) mean, minimum and maximum weight along the boundary of each edge.
+    """
+
+    labels: ArrayLike
+    area: ArrayLike
+    centroids: ArrayLike
+    edges: ArrayLike
+    boundary_length: ArrayLike
+    node_weights: ArrayLike | None
+    edge_weights: ArrayLike | None
+
+    def to_sparse(self, attribute: str = "boundary_length") -> Any:
+        """
+        Symmetric adjacency matrix of the graph, requires scipy.
+
+        Parameters
+        ----------
+        attribute : str
+            Edge values, "boundary_length", "mean", "min" or "max" of the boundary weights.
+
+        Returns
+        -------
+        scipy.sparse.csr_matrix
+            (n_nodes, n_nodes) matrix indexed by node indices.
+        """
+        from scipy import sparse
+
+        statistics = ("mean", "min", "max")
+        if attribute == "boundary_length":
+            values = self.boundary_length
+        elif attribute in statistics:
+            if self.edge_weights is None:
+                raise ValueError(f"Attribute {attribute} requires a weight image.")
+            values = self.edge_weights[:, statistics.index(attribute)]
+        else:
+            raise ValueError(f"Unknown attribute {attribute}, must be 'boundary_length', 'mean', 'min' or 'max'.")
+
+        n_nodes = len(self.labels)
+        rows = np.concatenate((self.edges[:, 0], self.edges[:, 1]))
+        cols = np.concatenate((self.edges[:, 1], self.edges[:, 0]))
+        values = np.concatenate((values, values))
+
+        return sparse.csr_matrix((values, (rows, cols)), shape=(n_nodes, n_nodes))
+
+
+def region_adjacency_graph(
+    labels: ArrayLike,
+    weights: ArrayLike | None = None,
+    connectivity: int | None = None,
+) -> RegionAdjacencyGraph:
+    """
+    Build the region adjacency graph of a label image, e.g. the output of `watershed_from_minima`.
+
+    Parameters
+    ----------
+    labels : ArrayLike
+        2 or 3-D non-negative integer label image, 0 is background.
+    weights : ArrayLike, optional
+        Image of the same shape as `labels` (e.g. the watershed topology) used for the node and boundary statistics.
+    connectivity : int, optional
+        Number of neighbors, 4 or 8 in 2D and 6, 18 or 26 in 3D. If `None`, 4 (2D) or 6 (3D) is used.
+
+    Returns
+    -------
+    RegionAdjacencyGraph
+        Nodes with their region statistics and edges with their boundary statistics.
+    """
+    labels = np.asarray(labels)
+
+    if labels.ndim != 2 and labels.ndim != 3:
+        raise ValueError(f"Unsupported dimension {labels.ndim}, must be 2 or 3.")
+
+    if not np.issubdtype(labels.dtype, np.integer):
+        raise ValueError(f"Labels must be integers. Found {labels.dtype}.")
+
+    if labels.size > 0 and labels.min() < 0:
+        raise ValueError("Labels must be non-negative.")
+
+    labels = np.ascontiguousarray(labels, dtype=np.uintp)
+
+    if weights is not None:
+        weights = np.ascontiguousarray(weights, dtype=np.float64)
+        if weights.shape != labels.shape:
+            raise ValueError(f"Weights shape {weights.shape} must match labels shape {labels.shape}.")
+
+    if connectivity is None:
+        connectivity = 2 * labels.ndim
+
+    valid_connectivity = (4, 8) if labels.ndim == 2 else (6, 18, 26)
+    if connectivity not in valid_connectivity:
+        raise ValueError(f"Connectivity must be one of {valid_connectivity}. Found {connectivity} instead.")
+
+    return RegionAdjacencyGraph(*REGION_ADJACENCY_GRAPH[labels.ndim](labels, connectivity, weights))
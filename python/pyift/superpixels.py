@@ -0,0 +1,115 @@
+import numpy as np
+from numpy.typing import ArrayLike
+from pyift import _rust_ift
+
+ISF_SUPERPIXELS = {
+    2: _rust_ift.isf_superpixels_2d,
+    3: _rust_ift.isf_superpixels_3d,
+}
+
+DISF_SUPERPIXELS = {
+    2: _rust_ift.disf_superpixels_2d,
+    3: _rust_ift.disf_superpixels_3d,
+}
+
+
+def _as_multichannel(image: ArrayLike, channel_axis: int | None) -> np.ndarray:
+    image = np.asarray(image, dtype=np.float64)
+
+    if channel_axis is None:
+        image = image[..., np.newaxis]
+    else:
+        image = np.moveaxis(image, channel_axis, -1)
+
+    if image.ndim != 3 and image.ndim != 4:
+        raise ValueError(f"Unsupported dimension {image.ndim - 1}, must be 2 or 3.")
+
+    return np.ascontiguousarray(image)
+
+
+def isf_superpixels(
+    image: ArrayLike,
+    n_superpixels: int,
+    compactness: float = 1.0,
+    n_iterations: int = 10,
+    channel_axis: int | None = None,
+) -> ArrayLike:
+    """
+    Compute superpixels (or supervoxels) with the iterative spanning forest (ISF).
+
+    The IFT is run from seeds placed on a regular grid with the path cost summing, along the path, the Euclidean
+    distance between the pixel features and the features of its seed plus `compactness`. Each seed is then moved
+    to the pixel of its superpixel closest to the superpixel centroid and takes its mean features, until the seeds
+    converge or `n_iterations` is reached.
+
+    Parameters
+    ----------
+    image : ArrayLike
+        2 or 3-D grayscale or multichannel image.
+    n_superpixels : int
+        Approximate number of superpixels.
+    compactness : float
+        Weight of the path length relative to the feature distances, larger values give more regular superpixels.
+    n_iterations : int
+        Maximum number of iterations.
+    channel_axis : int, optional
+        Axis of the image channels. If `None`, the image is considered grayscale.
+
+    Returns
+    -------
+    ArrayLike
+        Superpixel labels starting from 1.
+    """
+    image = _as_multichannel(image, channel_axis)
+
+    if n_superpixels < 1:
+        raise ValueError(f"`n_superpixels` must be positive. Found {n_superpixels}.")
+
+    if compactness < 0:
+        raise ValueError(f"`compactness` must be non-negative. Found {compactness}.")
+
+    if n_iterations < 1:
+        raise ValueError(f"`n_iterations` must be positive. Found {n_iterations}.")
+
+    return ISF_SUPERPIXELS[image.ndim - 1](image, n_superpixels, float(compactness), n_iterations)
+
+
+def disf_superpixels(
+    image: ArrayLike,
+    n_superpixels: int,
+    n_initial_seeds: int = 8000,
+    channel_axis: int | None = None,
+) -> ArrayLike:
+    """
+    Compute superpixels (or supervoxels) with the dynamic and iterative spanning forest (DISF).
+
+    The dynamic-tree IFT is run from an oversampled grid of seeds and, at each iteration, only the most relevant
+    seeds are kept until `n_superpixels` remain. The relevance of a superpixel is its relative size times the
+    smallest distance between its mean features and those of its adjacent superpixels, which gives good boundary
+    adherence even for very few superpixels.
+
+    Parameters
+    ----------
+    image : ArrayLike
+        2 or 3-D grayscale or multichannel image.
+    n_superpixels : int
+        Number of superpixels.
+    n_initial_seeds : int
+        Approximate number of seeds of the first iteration, it is at least `n_superpixels`.
+    channel_axis : int, optional
+        Axis of the image channels. If `None`, the image is considered grayscale.
+
+    Returns
+    -------
+    ArrayLike
+        Superpixel labels starting from 1.
+    """
+    image = _as_multichannel(image, channel_axis)
+
+    if n_superpixels < 1:
+        raise ValueError(f"`n_superpixels` must be positive. Found {n_superpixels}.")
+
+    if n_initial_seeds < 1:
+        raise ValueError(f"`n_initial_seeds` must be positive. Found {n_initial_seeds}.")
+
+    return DISF_SUPERPIXELS[image.ndim - 1](image, n_superpixels, n_initial_seeds)
@@ -1,6 +1,7 @@
 import numpy as np
 from numpy.typing import ArrayLike
 from pyift import _rust_ift
+from pyift.forest import OptimumPathForest
 
 WS_FROM_MINIMA = {
     ("uint8", 2): _rust_ift.watershed_from_minima_u8_2d,
@@ -21,14 +22,88 @@ WS_FROM_MINIMA = {
     ("float64", 3): _rust_ift.watershed_from_minima_f64_3d,
 }
 
+WS_FROM_MARKERS = {
+    ("uint8", 2): _rust_ift.watershed_from_markers_u8_2d,
+    ("uint8", 3): _rust_ift.watershed_from_markers_u8_3d,
+    ("uint16", 2): _rust_ift.watershed_from_markers_u16_2d,
+    ("uint16", 3): _rust_ift.watershed_from_markers_u16_3d,
+    ("uint32", 2): _rust_ift.watershed_from_markers_u32_2d,
+    ("uint32", 3): _rust_ift.watershed_from_markers_u32_3d,
+    ("int16", 2): _rust_ift.watershed_from_markers_i16_2d,
+    ("int16", 3): _rust_ift.watershed_from_markers_i16_3d,
+    ("int32", 2): _rust_ift.watershed_from_markers_i32_2d,
+    ("int32", 3): _rust_ift.watershed_from_markers_i32_3d,
+    ("int64", 2): _rust_ift.watershed_from_markers_i64_2d,
+    ("int64", 3): _rust_ift.watershed_from_markers_i64_3d,
+    ("float32", 2): _rust_ift.watershed_from_markers_f32_2d,
+    ("float32", 3): _rust_ift.watershed_from_markers_f32_3d,
+    ("float64", 2): _rust_ift.watershed_from_markers_f64_2d,
+    ("float64", 3): _rust_ift.watershed_from_markers_f64_3d,
+}
+
+COMPACT_WS = {
+    ("uint8", 2): _rust_ift.compact_watershed_u8_2d,
+    ("uint8", 3): _rust_ift.compact_watershed_u8_3d,
+    ("uint16", 2): _rust_ift.compact_watershed_u16_2d,
+    ("uint16", 3): _rust_ift.compact_watershed_u16_3d,
+    ("uint32", 2): _rust_ift.compact_watershed_u32_2d,
+    ("uint32", 3): _rust_ift.compact_watershed_u32_3d,
+    ("int16", 2): _rust_ift.compact_watershed_i16_2d,
+    ("int16", 3): _rust_ift.compact_watershed_i16_3d,
+    ("int32", 2): _rust_ift.compact_watershed_i32_2d,
+    ("int32", 3): _rust_ift.compact_watershed_i32_3d,
+    ("int64", 2): _rust_ift.compact_watershed_i64_2d,
+    ("int64", 3): _rust_ift.compact_watershed_i64_3d,
+    ("float32", 2): _rust_ift.compact_watershed_f32_2d,
+    ("float32", 3): _rust_ift.compact_watershed_f32_3d,
+    ("float64", 2): _rust_ift.compact_watershed_f64_2d,
+    ("float64", 3): _rust_ift.compact_watershed_f64_3d,
+}
+
+ARC_WEIGHT_WS_FROM_MINIMA = {
+    2: _rust_ift.arc_weight_watershed_from_minima_2d,
+    3: _rust_ift.arc_weight_watershed_from_minima_3d,
+}
+
+ARC_WEIGHT_WS_FROM_MARKERS = {
+    2: _rust_ift.arc_weight_watershed_from_markers_2d,
+    3: _rust_ift.arc_weight_watershed_from_markers_3d,
+}
+
+ARC_WEIGHTS = ("l2", "l1", "max")
+
+DIFFERENTIAL_WS = {
+    "uint8": _rust_ift.DifferentialWatershedU8,
+    "uint16": _rust_ift.DifferentialWatershedU16,
+    "uint32": _rust_ift.DifferentialWatershedU32,
+    "int16": _rust_ift.DifferentialWatershedI16,
+    "int32": _rust_ift.DifferentialWatershedI32,
+    "int64": _rust_ift.DifferentialWatershedI64,
+    "float32": _rust_ift.DifferentialWatershedF32,
+    "float64": _rust_ift.DifferentialWatershedF64,
+}
+
+DYNAMIC_TREE_WS = {
+    2: _rust_ift.dynamic_tree_watershed_2d,
+    3: _rust_ift.dynamic_tree_watershed_3d,
+}
+
+ORIENTED_WS = {
+    2: _rust_ift.oriented_watershed_2d,
+    3: _rust_ift.oriented_watershed_3d,
+}
+
 
 def watershed_from_minima(
     topology: ArrayLike,
     mask: ArrayLike | None,
     h: int | float,
-) -> ArrayLike:
+    watershed_line: bool = False,
+    from_maxima: bool = False,
+    return_forest: bool = False,
+) -> ArrayLike | OptimumPathForest:
     """
-    Compute the watershed transform from minima of a grayscale image (topology).
+    Compute the watershed transform from minima (or maxima) of a grayscale image (topology).
 
     Parameters
     ----------
@@ -38,11 +113,20 @@ def watershed_from_minima(
         Binary mask of the same shape as `topology`. If `None`, the whole image is considered.
     h : int or float
         Minimum height of the catchment basins.
+    watershed_line : bool
+        If `True`, pixels where two different basins meet during propagation are labeled 0,
+        forming one-pixel-thick separating lines on the 4 (2D) or 6 (3D) neighborhood.
+        Line pixels have no root nor predecessor in the returned forest.
+    from_maxima : bool
+        If `True`, the basins grow from the maxima with dynamics above `h` and flood the topology downwards, e.g.
+        for probability maps or distance transforms, without inverting the image.
+    return_forest : bool
+        If `True`, returns the full optimum-path forest instead of the labels only.
 
     Returns
     -------
-    ArrayLike
-        Labels from the watershed transform of the image.
+    ArrayLike or OptimumPathForest
+        Labels from the watershed transform of the image, or its optimum-path forest.
     """
     if mask is None:
         mask = np.ones_like(topology, dtype=bool)
@@ -59,4 +143,411 @@ def watershed_from_minima(
         else:
             raise ValueError(f"Unsupported dtype {topology.dtype}.")
 
-    return ws_func(topology, mask, h)
+    forest = OptimumPathForest(*ws_func(topology, mask, h, watershed_line, from_maxima))
+
+    if return_forest:
+        return forest
+
+    return forest.labels
+
+
+def watershed_from_markers(
+    topology: ArrayLike,
+    markers: ArrayLike,
+    mask: ArrayLike | None = None,
+    watershed_line: bool = False,
+    compactness: float = 0.0,
+    from_maxima: bool = False,
+    return_forest: bool = False,
+) -> ArrayLike | OptimumPathForest:
+    """
+    Compute the watershed transform of a grayscale image (topology) from user-supplied markers.
+
+    Parameters
+    ----------
+    topology : ArrayLike
+        2 or 3-D grayscale image.
+    markers : ArrayLike
+        Integer label image of the same shape as `topology`. Nonzero pixels are used as seeds.
+    mask : ArrayLike, optional
+        Binary mask of the same shape as `topology`. If `None`, the whole image is considered.
+    watershed_line : bool
+        If `True`, pixels where two different basins meet during propagation are labeled 0,
+        forming one-pixel-thick separating lines on the 4 (2D) or 6 (3D) neighborhood.
+        Line pixels have no root nor predecessor in the returned forest.
+    compactness : float
+        If positive, pixels are flooded in the order of their topology value plus `compactness` times their
+        Euclidean distance to the marker pixel rooting their basin, giving more regular basins (compact watershed).
+        The forest costs are then float64.
+    from_maxima : bool
+        If `True`, the markers flood the topology downwards, following its high values, without inverting the image.
+    return_forest : bool
+        If `True`, returns the full optimum-path forest instead of the labels only.
+
+    Returns
+    -------
+    ArrayLike or OptimumPathForest
+        Labels from the watershed transform of the image, each pixel receives the label of its marker,
+        or its optimum-path forest.
+    """
+    markers = np.asarray(markers)
+
+    if mask is None:
+        mask = np.ones_like(topology, dtype=bool)
+
+    if mask.shape != topology.shape:
+        raise ValueError(
+            f"Mask and topology must have the same shape. Found {mask.shape} and {topology.shape} instead."
+        )
+
+    if markers.shape != topology.shape:
+        raise ValueError(
+            f"Markers and topology must have the same shape. Found {markers.shape} and {topology.shape} instead."
+        )
+
+    if np.any(markers < 0):
+        raise ValueError("Markers must be non-negative.")
+
+    if compactness < 0:
+        raise ValueError(f"Compactness must be non-negative. Found {compactness}.")
+
+    if compactness > 0 and from_maxima:
+        raise ValueError("Compact watershed from maxima is not supported.")
+
+    ws_func = WS_FROM_MARKERS.get((str(topology.dtype), topology.ndim))
+    if ws_func is None:
+        if topology.ndim != 2 and topology.ndim != 3:
+            raise ValueError(f"Unsupported dimension {topology.ndim}, must be 2 or 3.")
+        else:
+            raise ValueError(f"Unsupported dtype {topology.dtype}.")
+
+    markers = np.ascontiguousarray(markers, dtype=np.uintp)
+
+    if compactness > 0:
+        compact_func = COMPACT_WS[(str(topology.dtype), topology.ndim)]
+        forest = OptimumPathForest(*compact_func(topology, markers, mask, float(compactness), watershed_line))
+    else:
+        forest = OptimumPathForest(*ws_func(topology, markers, mask, watershed_line, from_maxima))
+
+    if return_forest:
+        return forest
+
+    return forest.labels
+
+
+def dynamic_tree_watershed(
+    image: ArrayLike,
+    markers: ArrayLike,
+    mask: ArrayLike | None = None,
+    channel_axis: int | None = None,
+    return_forest: bool = False,
+) -> ArrayLike | OptimumPathForest:
+    """
+    Compute the watershed transform from markers with dynamic trees.
+
+    The weight of the arc (p, q) is the Euclidean distance between the features of `q` and the mean features of
+    the tree being grown from `p`, which gives better boundaries on heterogeneous objects.
+
+    Parameters
+    ----------
+    image : ArrayLike
+        2 or 3-D grayscale or multichannel image.
+    markers : ArrayLike
+        Integer label image with the spatial shape of `image`. Nonzero pixels are used as seeds.
+    mask : ArrayLike, optional
+        Binary mask with the spatial shape of `image`. If `None`, the whole image is considered.
+    channel_axis : int, optional
+        Axis of the image channels. If `None`, the image is considered grayscale.
+    return_forest : bool
+        If `True`, returns the full optimum-path forest instead of the labels only.
+
+    Returns
+    -------
+    ArrayLike or OptimumPathForest
+        Labels from the watershed transform of the image, or its optimum-path forest.
+    """
+    image = np.asarray(image, dtype=np.float64)
+    markers = np.asarray(markers)
+
+    if channel_axis is None:
+        image = image[..., np.newaxis]
+    else:
+        image = np.moveaxis(image, channel_axis, -1)
+
+    if image.shape[:-1] != markers.shape:
+        raise ValueError(
+            f"Image and markers must have the same spatial shape. Found {image.shape[:-1]} and {markers.shape} instead."
+        )
+
+    if mask is None:
+        mask = np.ones_like(markers, dtype=bool)
+
+    if mask.shape != markers.shape:
+        raise ValueError(
+            f"Mask and markers must have the same shape. Found {mask.shape} and {markers.shape} instead."
+        )
+
+    if np.any(markers < 0):
+        raise ValueError("Markers must be non-negative.")
+
+    ws_func = DYNAMIC_TREE_WS.get(markers.ndim)
+    if ws_func is None:
+        raise ValueError(f"Unsupported dimension {markers.ndim}, must be 2 or 3.")
+
+    image = np.ascontiguousarray(image)
+    markers = np.ascontiguousarray(markers, dtype=np.uintp)
+
+    forest = OptimumPathForest(*ws_func(image, markers, mask))
+
+    if return_forest:
+        return forest
+
+    return forest.labels
+
+
+def oriented_watershed(
+    image: ArrayLike,
+    markers: ArrayLike,
+    mask: ArrayLike | None = None,
+    background_label: int = 1,
+    alpha: float = 0.5,
+    return_forest: bool = False,
+) -> ArrayLike | OptimumPathForest:
+    """
+    Compute the oriented watershed transform from markers, which enforces the boundary polarity.
+
+    The weight of the arc (p, q) is |I(q) - I(p)|, scaled by (1 + alpha) on bright to dark transitions of object
+    trees and on dark to bright transitions of background trees, and by (1 - alpha) on the opposite ones.
+
+    Parameters
+    ----------
+    image : ArrayLike
+        2 or 3-D grayscale image.
+    markers : ArrayLike
+        Integer label image of the same shape as `image`. Nonzero pixels are used as seeds.
+    mask : ArrayLike, optional
+        Binary mask of the same shape as `image`. If `None`, the whole image is considered.
+    background_label : int
+        Label of the background markers, all the other labels are objects.
+    alpha : float
+        Polarity in [-1, 1]. Positive values favor boundaries where objects are brighter than the background,
+        negative values favor darker objects and zero ignores the polarity.
+    return_forest : bool
+        If `True`, returns the full optimum-path forest instead of the labels only.
+
+    Returns
+    -------
+    ArrayLike or OptimumPathForest
+        Labels from the oriented watershed transform of the image, or its optimum-path forest.
+    """
+    image = np.asarray(image, dtype=np.float64)
+    markers = np.asarray(markers)
+
+    if mask is None:
+        mask = np.ones_like(image, dtype=bool)
+
+    if mask.shape != image.shape:
+        raise ValueError(f"Mask and image must have the same shape. Found {mask.shape} and {image.shape} instead.")
+
+    if markers.shape != image.shape:
+        raise ValueError(
+            f"Markers and image must have the same shape. Found {markers.shape} and {image.shape} instead."
+        )
+
+    if np.any(markers < 0):
+        raise ValueError("Markers must be non-negative.")
+
+    if not -1.0 <= alpha <= 1.0:
+        raise ValueError(f"alpha must be in [-1, 1]. Found {alpha} instead.")
+
+    ws_func = ORIENTED_WS.get(image.ndim)
+    if ws_func is None:
+        raise ValueError(f"Unsupported dimension {image.ndim}, must be 2 or 3.")
+
+    markers = np.ascontiguousarray(markers, dtype=np.uintp)
+
+    forest = OptimumPathForest(*ws_func(image, markers, mask, background_label, alpha))
+
+    if return_forest:
+        return forest
+
+    return forest.labels
+
+
+def arc_weight_watershed(
+    image: ArrayLike,
+    markers: ArrayLike | None = None,
+    mask: ArrayLike | None = None,
+    h: float | None = None,
+    arc_weight: str = "l2",
+    channel_axis: int | None = -1,
+    watershed_line: bool = False,
+    return_forest: bool = False,
+) -> ArrayLike | OptimumPathForest:
+    """
+    Compute the watershed transform of a multichannel image without a precomputed topology.
+
+    The weight of the arc (p, q) is computed on the fly as the distance between the feature vectors of `p` and
+    `q`, which avoids building a gradient image and gives sharper boundaries than a per-pixel gradient.
+
+    Parameters
+    ----------
+    image : ArrayLike
+        2 or 3-D grayscale or multichannel image.
+    markers : ArrayLike, optional
+        Integer label image with the spatial shape of `image`. Nonzero pixels are used as seeds.
+        If `None`, the basins grow from the regional minima of the arc weights and `h` is required.
+    mask : ArrayLike, optional
+        Binary mask with the spatial shape of `image`. If `None`, the whole image is considered.
+    h : float, optional
+        Minimum height of the catchment basins when growing from minima.
+    arc_weight : str
+        "l2" for the Euclidean distance, "l1" for the sum of the absolute channel differences or "max" for the
+        largest absolute channel difference.
+    channel_axis : int, optional
+        Axis of the image channels, the last one by default. If `None`, the image is considered grayscale.
+    watershed_line : bool
+        If `True`, pixels where two different basins meet during propagation are labeled 0,
+        forming one-pixel-thick separating lines on the 4 (2D) or 6 (3D) neighborhood.
+        Line pixels have no root nor predecessor in the returned forest.
+    return_forest : bool
+        If `True`, returns the full optimum-path forest instead of the labels only.
+
+    Returns
+    -------
+    ArrayLike or OptimumPathForest
+        Labels from the watershed transform of the image, or its optimum-path forest.
+    """
+    image = np.asarray(image, dtype=np.float64)
+
+    if channel_axis is None:
+        image = image[..., np.newaxis]
+    else:
+        image = np.moveaxis(image, channel_axis, -1)
+
+    spatial_shape = image.shape[:-1]
+    if len(spatial_shape) != 2 and len(spatial_shape) != 3:
+        raise ValueError(f"Unsupported dimension {len(spatial_shape)}, must be 2 or 3.")
+
+    if arc_weight not in ARC_WEIGHTS:
+        raise ValueError(f"Unknown arc weight {arc_weight}, must be one of {ARC_WEIGHTS}.")
+
+    if mask is None:
+        mask = np.ones(spatial_shape, dtype=bool)
+
+    if mask.shape != spatial_shape:
+        raise ValueError(
+            f"Mask and image must have the same spatial shape. Found {mask.shape} and {spatial_shape} instead."
+        )
+
+    image = np.ascontiguousarray(image)
+
+    if markers is None:
+        if h is None or h <= 0:
+            raise ValueError(f"`h` must be positive when no markers are given. Found {h}.")
+
+        ws_func = ARC_WEIGHT_WS_FROM_MINIMA[len(spatial_shape)]
+        forest = OptimumPathForest(*ws_func(image, mask, float(h), arc_weight, watershed_line))
+    else:
+        markers = np.asarray(markers)
+
+        if markers.shape != spatial_shape:
+            raise ValueError(
+                f"Markers and image must have the same spatial shape. Found {markers.shape} and {spatial_shape}."
+            )
+
+        if np.any(markers < 0):
+            raise ValueError("Markers must be non-negative.")
+
+        markers = np.ascontiguousarray(markers, dtype=np.uintp)
+
+        ws_func = ARC_WEIGHT_WS_FROM_MARKERS[len(spatial_shape)]
+        forest = OptimumPathForest(*ws_func(image, markers, mask, arc_weight, watershed_line))
+
+    if return_forest:
+        return forest
+
+    return forest.labels
+
+
+class DifferentialWatershed:
+    """
+    Interactive watershed from markers using the differential IFT (DIFT).
+
+    The optimum-path forest is kept between calls, adding or removing seeds only recomputes the affected trees.
+
+    Parameters
+    ----------
+    topology : ArrayLike
+        2 or 3-D grayscale image.
+    mask : ArrayLike, optional
+        Binary mask of the same shape as `topology`. If `None`, the whole image is considered.
+    """
+
+    def __init__(self, topology: ArrayLike, mask: ArrayLike | None = None) -> None:
+        topology = np.asarray(topology)
+
+        if topology.ndim != 2 and topology.ndim != 3:
+            raise ValueError(f"Unsupported dimension {topology.ndim}, must be 2 or 3.")
+
+        if mask is None:
+            mask = np.ones_like(topology, dtype=bool)
+
+        if mask.shape != topology.shape:
+            raise ValueError(
+                f"Mask and topology must have the same shape. Found {mask.shape} and {topology.shape} instead."
+            )
+
+        dift_cls = DIFFERENTIAL_WS.get(str(topology.dtype))
+        if dift_cls is None:
+            raise ValueError(f"Unsupported dtype {topology.dtype}.")
+
+        self._shape = topology.shape
+        self._dift = dift_cls(topology, mask)
+
+    def _to_indices(self, coords: ArrayLike) -> list[int]:
+        coords = np.atleast_2d(coords)
+        if coords.shape[1] != len(self._shape):
+            raise ValueError(f"Coordinates must have shape (N, {len(self._shape)}). Found {coords.shape} instead.")
+        return np.ravel_multi_index(tuple(coords.T), self._shape).tolist()
+
+    def add_seeds(self, coords: ArrayLike, labels: ArrayLike) -> None:
+        """
+        Add seeds and update the segmentation, seeds on existing seeds replace them.
+
+        Parameters
+        ----------
+        coords : ArrayLike
+            (N, ndim) array of seed coordinates.
+        labels : ArrayLike
+            (N,) array of positive seed labels.
+        """
+        indices = self._to_indices(coords)
+        labels = np.atleast_1d(labels)
+
+        if len(labels) != len(indices):
+            raise ValueError(f"Expected {len(indices)} labels. Found {len(labels)} instead.")
+
+        if np.any(labels <= 0):
+            raise ValueError("Labels must be positive.")
+
+        self._dift.add_seeds(indices, labels.tolist())
+
+    def remove_seeds(self, coords: ArrayLike) -> None:
+        """
+        Remove seeds and their trees, and update the segmentation. Coordinates that are not seeds are ignored.
+
+        Parameters
+        ----------
+        coords : ArrayLike
+            (N, ndim) array of seed coordinates.
+        """
+        self._dift.remove_seeds(self._to_indices(coords))
+
+    def labels(self) -> ArrayLike:
+        """Current segmentation labels, zero for pixels not reached by any seed."""
+        return self._dift.labels()
+
+    def costs(self) -> ArrayLike:
+        """Current path costs."""
+        return self._dift.costs()
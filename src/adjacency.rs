@@ -2,14 +2,149 @@ pub trait Adjacency {
     fn neighbors(&self, p: usize) -> Vec<usize>;
 }
 
+pub fn grid_adjacency(shape: &[usize]) -> Box<dyn Adjacency> {
+    match shape.len() {
+        2 => Box::new(AdjacencyGrid2D::new(shape)),
+        3 => Box::new(AdjacencyGrid3D::new(shape)),
+        _ => panic!("Unsupported dimension!"),
+    }
+}
+
+/// Grid adjacency with the given number of neighbors (4 or 8 in 2D, 6, 18 or 26 in 3D).
+pub fn grid_adjacency_with_connectivity(
+    shape: &[usize],
+    connectivity: usize,
+) -> Box<dyn Adjacency> {
+    match shape.len() {
+        2 => Box::new(AdjacencyGrid2D::with_connectivity(shape, connectivity)),
+        3 => Box::new(AdjacencyGrid3D::with_connectivity(shape, connectivity)),
+        _ => panic!("Unsupported dimension!"),
+    }
+}
+
+/// Squared euclidean distance between the flat indices `p` and `q` of a grid with the given `spacing`.
+pub fn squared_distance(shape: &[usize], spacing: &[f64], p: usize, q: usize) -> f64 {
+    let mut p = p;
+    let mut q = q;
+    let mut dist = 0.0;
+    for (&s, &sp) in shape.iter().zip(spacing).rev() {
+        let diff = ((p % s) as f64 - (q % s) as f64) * sp;
+        dist += diff * diff;
+        p /= s;
+        q /= s;
+    }
+    dist
+}
+
+/// Complete graph on `n` nodes, every node is adjacent to all the others.
+pub struct CompleteGraph {
+    n: usize,
+}
+
+impl CompleteGraph {
+    pub fn new(n: usize) -> Self {
+        CompleteGraph { n }
+    }
+}
+
+impl Adjacency for CompleteGraph {
+    fn neighbors(&self, p: usize) -> Vec<usize> {
+        (0..self.n).filter(|&q| q != p).collect()
+    }
+}
+
+/// Directed graph in compressed sparse row (CSR) format, the arcs leaving `p` go to
+/// `indices[indptr[p]..indptr[p + 1]]` with weights `data[indptr[p]..indptr[p + 1]]`.
+/// Rows are sorted by target and repeated arcs keep their smallest weight, so weights are found
+/// by binary search.
+pub struct CsrGraph {
+    indptr: Vec<usize>,
+    indices: Vec<usize>,
+    data: Vec<f64>,
+}
+
+impl CsrGraph {
+    pub fn new(indptr: Vec<usize>, indices: Vec<usize>, data: Vec<f64>) -> Self {
+        if indptr.is_empty() || indptr[0] != 0 || indptr.windows(2).any(|w| w[0] > w[1]) {
+            panic!("indptr must start at 0 and be non-decreasing");
+        }
+        if indices.len() != data.len() || *indptr.last().unwrap() != indices.len() {
+            panic!("indices and data must have indptr[-1] elements");
+        }
+        let n_nodes = indptr.len() - 1;
+        if indices.iter().any(|&q| q >= n_nodes) {
+            panic!("indices must be smaller than the number of nodes");
+        }
+
+        let mut sorted_indptr = Vec::with_capacity(indptr.len());
+        let mut sorted_indices = Vec::with_capacity(indices.len());
+        let mut sorted_data = Vec::with_capacity(data.len());
+        sorted_indptr.push(0);
+        for p in 0..n_nodes {
+            let mut row: Vec<(usize, f64)> = (indptr[p]..indptr[p + 1])
+                .map(|i| (indices[i], data[i]))
+                .collect();
+            row.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)));
+            row.dedup_by_key(|arc| arc.0);
+            for (q, w) in row {
+                sorted_indices.push(q);
+                sorted_data.push(w);
+            }
+            sorted_indptr.push(sorted_indices.len());
+        }
+
+        CsrGraph {
+            indptr: sorted_indptr,
+            indices: sorted_indices,
+            data: sorted_data,
+        }
+    }
+
+    pub fn n_nodes(&self) -> usize {
+        self.indptr.len() - 1
+    }
+
+    /// Weight of the arc (p, q), the smallest one when there were repeated arcs.
+    pub fn weight(&self, p: usize, q: usize) -> Option<f64> {
+        let start = self.indptr[p];
+        self.indices[start..self.indptr[p + 1]]
+            .binary_search(&q)
+            .ok()
+            .map(|i| self.data[start + i])
+    }
+
+    pub fn weights(&self) -> &[f64] {
+        &self.data
+    }
+}
+
+impl Adjacency for CsrGraph {
+    fn neighbors(&self, p: usize) -> Vec<usize> {
+        self.indices[self.indptr[p]..self.indptr[p + 1]].to_vec()
+    }
+}
+
 pub struct AdjacencyGrid2D {
     shape: [usize; 2],
+    // (x, y) displacements
+    offsets: Vec<[isize; 2]>,
 }
 
 impl AdjacencyGrid2D {
     pub fn new(shape: &[usize]) -> Self {
+        AdjacencyGrid2D::with_connectivity(shape, 4)
+    }
+
+    pub fn with_connectivity(shape: &[usize], connectivity: usize) -> Self {
+        let mut offsets = vec![[0, 1], [1, 0], [0, -1], [-1, 0]];
+        match connectivity {
+            4 => {}
+            8 => offsets.extend([[1, 1], [1, -1], [-1, -1], [-1, 1]]),
+            _ => panic!("Connectivity must be 4 or 8 for 2D grids!"),
+        }
         AdjacencyGrid2D {
             shape: [shape[0], shape[1]],
+            offsets,
         }
     }
 
@@ -31,34 +166,14 @@ impl Adjacency for AdjacencyGrid2D {
         // array of neighbors
 
         // wraps around so validation is done upper bound only
-        let mut valid_neighbors = Vec::<usize>::with_capacity(4);
-
-        // (0, 1)
-        let ny = y.wrapping_add(1);
-        let nx = x;
-        if self.is_valid(nx, ny) {
-            valid_neighbors.push(self.to_index(nx, ny));
-        }
-
-        // (1, 0)
-        let ny = y;
-        let nx = x.wrapping_add(1);
-        if self.is_valid(nx, ny) {
-            valid_neighbors.push(self.to_index(nx, ny));
-        }
-
-        // (0, -1)
-        let ny = y.wrapping_sub(1);
-        let nx = x;
-        if self.is_valid(nx, ny) {
-            valid_neighbors.push(self.to_index(nx, ny));
-        }
-
-        // (-1, 0)
-        let ny = y;
-        let nx = x.wrapping_sub(1);
-        if self.is_valid(nx, ny) {
-            valid_neighbors.push(self.to_index(nx, ny));
+        let mut valid_neighbors = Vec::<usize>::with_capacity(self.offsets.len());
+
+        for &[dx, dy] in &self.offsets {
+            let nx = x.wrapping_add_signed(dx);
+            let ny = y.wrapping_add_signed(dy);
+            if self.is_valid(nx, ny) {
+                valid_neighbors.push(self.to_index(nx, ny));
+            }
         }
 
         valid_neighbors
@@ -67,12 +182,43 @@ impl Adjacency for AdjacencyGrid2D {
 
 pub struct AdjacencyGrid3D {
     shape: [usize; 3],
+    // (x, y, z) displacements
+    offsets: Vec<[isize; 3]>,
 }
 
 impl AdjacencyGrid3D {
     pub fn new(shape: &[usize]) -> Self {
+        AdjacencyGrid3D::with_connectivity(shape, 6)
+    }
+
+    pub fn with_connectivity(shape: &[usize], connectivity: usize) -> Self {
+        let max_nonzero = match connectivity {
+            6 => 1,
+            18 => 2,
+            26 => 3,
+            _ => panic!("Connectivity must be 6, 18 or 26 for 3D grids!"),
+        };
+        let mut offsets = vec![
+            [0, 0, 1],
+            [0, 1, 0],
+            [1, 0, 0],
+            [0, 0, -1],
+            [0, -1, 0],
+            [-1, 0, 0],
+        ];
+        for dz in -1..=1 {
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    let nonzero = [dx, dy, dz].iter().filter(|&&d| d != 0).count();
+                    if nonzero > 1 && nonzero <= max_nonzero {
+                        offsets.push([dx, dy, dz]);
+                    }
+                }
+            }
+        }
         AdjacencyGrid3D {
             shape: [shape[0], shape[1], shape[2]],
+            offsets,
         }
     }
 
@@ -95,54 +241,15 @@ impl Adjacency for AdjacencyGrid3D {
         // array of neighbors
 
         // wraps around so validation is done upper bound only
-        let mut valid_neighbors = Vec::<usize>::with_capacity(6);
-
-        // (0, 0, 1)
-        let nz = z.wrapping_add(1);
-        let ny = y;
-        let nx = x;
-        if self.is_valid(nx, ny, nz) {
-            valid_neighbors.push(self.to_index(nx, ny, nz));
-        }
-
-        // (0, 1, 0)
-        let nz = z;
-        let ny = y.wrapping_add(1);
-        let nx = x;
-        if self.is_valid(nx, ny, nz) {
-            valid_neighbors.push(self.to_index(nx, ny, nz));
-        }
-
-        // (1, 0, 0)
-        let nz = z;
-        let ny = y;
-        let nx = x.wrapping_add(1);
-        if self.is_valid(nx, ny, nz) {
-            valid_neighbors.push(self.to_index(nx, ny, nz));
-        }
-
-        // (0, 0, -1)
-        let nz = z.wrapping_sub(1);
-        let ny = y;
-        let nx = x;
-        if self.is_valid(nx, ny, nz) {
-            valid_neighbors.push(self.to_index(nx, ny, nz));
-        }
-
-        // (0, -1, 0)
-        let nz = z;
-        let ny = y.wrapping_sub(1);
-        let nx = x;
-        if self.is_valid(nx, ny, nz) {
-            valid_neighbors.push(self.to_index(nx, ny, nz));
-        }
-
-        // (-1, 0, 0)
-        let nz = z;
-        let ny = y;
-        let nx = x.wrapping_sub(1);
-        if self.is_valid(nx, ny, nz) {
-            valid_neighbors.push(self.to_index(nx, ny, nz));
+        let mut valid_neighbors = Vec::<usize>::with_capacity(self.offsets.len());
+
+        for &[dx, dy, dz] in &self.offsets {
+            let nx = x.wrapping_add_signed(dx);
+            let ny = y.wrapping_add_signed(dy);
+            let nz = z.wrapping_add_signed(dz);
+            if self.is_valid(nx, ny, nz) {
+                valid_neighbors.push(self.to_index(nx, ny, nz));
+            }
         }
 
         valid_neighbors
@@ -197,3 +304,38 @@ fn test_3d_neighboorhood() {
     let neighbors = adj.neighbors(4);
     assert_eq!(neighbors.len(), 5);
 }
+
+#[test]
+fn test_grid_connectivity() {
+    let adj = AdjacencyGrid2D::with_connectivity(&[3, 3], 8);
+    let mut neighbors = adj.neighbors(4);
+    neighbors.sort();
+    assert_eq!(neighbors, vec![0, 1, 2, 3, 5, 6, 7, 8]);
+    assert_eq!(adj.neighbors(0).len(), 3);
+
+    let shape = [3, 3, 3];
+    assert_eq!(
+        AdjacencyGrid3D::with_connectivity(&shape, 18)
+            .neighbors(13)
+            .len(),
+        18
+    );
+    assert_eq!(
+        AdjacencyGrid3D::with_connectivity(&shape, 26)
+            .neighbors(13)
+            .len(),
+        26
+    );
+    assert_eq!(
+        AdjacencyGrid3D::with_connectivity(&shape, 26)
+            .neighbors(0)
+            .len(),
+        7
+    );
+    assert_eq!(
+        AdjacencyGrid3D::with_connectivity(&shape, 18)
+            .neighbors(0)
+            .len(),
+        6
+    );
+}
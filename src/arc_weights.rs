@@ -0,0 +1,279 @@
+use ndarray::*;
+use numpy::PyReadonlyArray;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::adjacency::grid_adjacency;
+use crate::forest::{OptimumPathForest, PyForest};
+use crate::ift::{
+    image_foresting_transform, image_foresting_transform_with_line, FMax, PathCost, Seed,
+};
+use crate::superpixels::spatial_dim;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ArcWeight {
+    /// Euclidean distance between the feature vectors.
+    L2,
+    /// Sum of the absolute channel differences.
+    L1,
+    /// Largest absolute channel difference.
+    MaxChannel,
+}
+
+impl ArcWeight {
+    #[inline(always)]
+    pub fn distance(&self, a: ArrayView1<f64>, b: ArrayView1<f64>) -> f64 {
+        let diffs = a.iter().zip(b).map(|(&x, &y)| (x - y).abs());
+        match self {
+            ArcWeight::L2 => diffs.map(|d| d * d).sum::<f64>().sqrt(),
+            ArcWeight::L1 => diffs.sum(),
+            ArcWeight::MaxChannel => diffs.fold(0.0, f64::max),
+        }
+    }
+}
+
+/// fmax on the arc weights between features, trivial paths have the `h` handicap over the lowest
+/// arc weight incident to the pixel until they root a tree.
+struct ArcWeightHMinima<'a> {
+    features: ArrayView2<'a, f64>,
+    arc_weight: ArcWeight,
+    min_weights: Vec<f64>,
+}
+
+impl<'a> PathCost<f64> for ArcWeightHMinima<'a> {
+    #[inline(always)]
+    fn extend(&self, p: usize, q: usize, cost_p: f64, _root: usize) -> f64 {
+        let w = self
+            .arc_weight
+            .distance(self.features.row(p), self.features.row(q));
+        cost_p.max(w)
+    }
+
+    fn root_cost(&self, p: usize, _cost: f64) -> f64 {
+        self.min_weights[p]
+    }
+}
+
+/// Features of each pixel of `image`, which has the channels on its last axis.
+fn as_features<'a, D: Dimension>(
+    image: &'a ArrayView<f64, D::Larger>,
+    shape: &D,
+) -> CowArray<'a, f64, Ix2> {
+    let n_channels = image.shape()[shape.ndim()];
+    image.to_shape((shape.size(), n_channels)).unwrap()
+}
+
+/// Watershed from the regional minima of the arc weights computed on the fly between the features
+/// of adjacent pixels, basins with dynamics below `h` are merged.
+/// `image` has the channels on its last axis.
+pub fn arc_weight_watershed_from_minima<D>(
+    image: &ArrayView<f64, D::Larger>,
+    mask: &ArrayView<bool, D>,
+    h: f64,
+    arc_weight: ArcWeight,
+    watershed_line: bool,
+) -> OptimumPathForest<f64, D>
+where
+    D: Dimension,
+{
+    if h <= 0.0 {
+        panic!("h must be greater than 0");
+    }
+
+    let shape: D = spatial_dim(image.shape());
+    if shape.slice() != mask.shape() {
+        panic!("Image and mask must have the same spatial shape");
+    }
+    let adj = grid_adjacency(shape.slice());
+
+    let features = as_features(image, &shape);
+    let mask = mask.to_shape(shape.size()).unwrap();
+
+    // altitude of each pixel, isolated pixels are at zero
+    let min_weights: Vec<f64> = (0..shape.size())
+        .map(|p| {
+            adj.neighbors(p)
+                .into_iter()
+                .filter(|&q| mask[q])
+                .map(|q| arc_weight.distance(features.row(p), features.row(q)))
+                .reduce(f64::min)
+                .unwrap_or(0.0)
+        })
+        .collect();
+
+    let seeds: Vec<Seed<f64>> = (0..shape.size())
+        .filter(|&i| mask[i])
+        .map(|i| Seed::new(i, min_weights[i] + h, i + 1))
+        .collect();
+
+    let mut path_cost = ArcWeightHMinima {
+        features: features.view(),
+        arc_weight,
+        min_weights,
+    };
+
+    let forest = if watershed_line {
+        image_foresting_transform_with_line(adj.as_ref(), &mut path_cost, &seeds, &mask.view())
+    } else {
+        image_foresting_transform(adj.as_ref(), &mut path_cost, &seeds, &mask.view())
+    };
+    forest.into_shape(shape)
+}
+
+/// Watershed from `markers` with the arc weights computed on the fly between the features of
+/// adjacent pixels, `image` has the channels on its last axis.
+pub fn arc_weight_watershed_from_markers<D>(
+    image: &ArrayView<f64, D::Larger>,
+    markers: &ArrayView<usize, D>,
+    mask: &ArrayView<bool, D>,
+    arc_weight: ArcWeight,
+    watershed_line: bool,
+) -> OptimumPathForest<f64, D>
+where
+    D: Dimension,
+{
+    let shape: D = spatial_dim(image.shape());
+    if shape.slice() != markers.shape() || shape.slice() != mask.shape() {
+        panic!("Image, markers and mask must have the same spatial shape");
+    }
+    let adj = grid_adjacency(shape.slice());
+
+    let features = as_features(image, &shape);
+    let markers = markers.to_shape(shape.size()).unwrap();
+    let mask = mask.to_shape(shape.size()).unwrap();
+
+    let seeds: Vec<Seed<f64>> = (0..shape.size())
+        .filter(|&i| mask[i] && markers[i] != 0)
+        .map(|i| Seed::new(i, 0.0, markers[i]))
+        .collect();
+
+    let mut path_cost =
+        FMax::new(|p: usize, q: usize| arc_weight.distance(features.row(p), features.row(q)));
+
+    let forest = if watershed_line {
+        image_foresting_transform_with_line(adj.as_ref(), &mut path_cost, &seeds, &mask.view())
+    } else {
+        image_foresting_transform(adj.as_ref(), &mut path_cost, &seeds, &mask.view())
+    };
+    forest.into_shape(shape)
+}
+
+#[test]
+fn test_arc_weight_watershed() {
+    let image = Array3::from_shape_fn(
+        (2, 6, 2),
+        |(_, x, c)| {
+            if x < 3 {
+                0.0
+            } else {
+                1.0 + c as f64
+            }
+        },
+    );
+    let mask = Array2::from_elem((2, 6), true);
+
+    let forest =
+        arc_weight_watershed_from_minima(&image.view(), &mask.view(), 0.5, ArcWeight::L2, false);
+    // each flat region is a minimum, labeled after whichever of its pixels roots it
+    let (left, right) = (forest.labels[[0, 0]], forest.labels[[0, 3]]);
+    assert_ne!(left, right);
+    assert!(forest.labels.slice(s![.., ..3]).iter().all(|&l| l == left));
+    assert!(forest.labels.slice(s![.., 3..]).iter().all(|&l| l == right));
+    assert_eq!(forest.costs, Array2::zeros((2, 6)));
+
+    // a ramp has no minimum deeper than h besides its start
+    let ramp = Array3::from_shape_fn((1, 6, 1), |(_, x, _)| x as f64);
+    let forest = arc_weight_watershed_from_minima(
+        &ramp.view(),
+        &Array2::from_elem((1, 6), true).view(),
+        0.5,
+        ArcWeight::L2,
+        false,
+    );
+    assert_eq!(forest.labels, Array2::from_elem((1, 6), 1));
+    assert_eq!(forest.costs, Array2::from_elem((1, 6), 1.0));
+
+    // a single marker crosses the edge, whose weight depends on the distance
+    let mut markers = Array2::<usize>::zeros((2, 6));
+    markers[[0, 0]] = 1;
+    for (arc_weight, distance) in [
+        (ArcWeight::L2, 5.0_f64.sqrt()),
+        (ArcWeight::L1, 3.0),
+        (ArcWeight::MaxChannel, 2.0),
+    ] {
+        let forest = arc_weight_watershed_from_markers(
+            &image.view(),
+            &markers.view(),
+            &mask.view(),
+            arc_weight,
+            false,
+        );
+        assert_eq!(forest.labels, Array2::from_elem((2, 6), 1));
+        assert_eq!(forest.costs[[1, 2]], 0.0);
+        assert_eq!(forest.costs[[1, 3]], distance);
+    }
+}
+
+fn parse_arc_weight(arc_weight: &str) -> PyResult<ArcWeight> {
+    match arc_weight {
+        "l2" => Ok(ArcWeight::L2),
+        "l1" => Ok(ArcWeight::L1),
+        "max" => Ok(ArcWeight::MaxChannel),
+        _ => Err(PyValueError::new_err(format!(
+            "Unknown arc weight {arc_weight}, must be 'l2', 'l1' or 'max'."
+        ))),
+    }
+}
+
+macro_rules! impl_arc_weight_watershed {
+    ($minima_name:ident, $markers_name:ident, $dim:expr) => {
+        #[pyfunction]
+        pub fn $minima_name<'py>(
+            py: Python<'py>,
+            image: PyReadonlyArray<f64, Dim<[usize; $dim + 1]>>,
+            mask: PyReadonlyArray<bool, Dim<[usize; $dim]>>,
+            h: f64,
+            arc_weight: &str,
+            watershed_line: bool,
+        ) -> PyResult<PyForest<'py, f64, Dim<[usize; $dim]>>> {
+            let forest = arc_weight_watershed_from_minima(
+                &image.as_array(),
+                &mask.as_array(),
+                h,
+                parse_arc_weight(arc_weight)?,
+                watershed_line,
+            );
+            Ok(forest.into_pyarrays(py))
+        }
+
+        #[pyfunction]
+        pub fn $markers_name<'py>(
+            py: Python<'py>,
+            image: PyReadonlyArray<f64, Dim<[usize; $dim + 1]>>,
+            markers: PyReadonlyArray<usize, Dim<[usize; $dim]>>,
+            mask: PyReadonlyArray<bool, Dim<[usize; $dim]>>,
+            arc_weight: &str,
+            watershed_line: bool,
+        ) -> PyResult<PyForest<'py, f64, Dim<[usize; $dim]>>> {
+            let forest = arc_weight_watershed_from_markers(
+                &image.as_array(),
+                &markers.as_array(),
+                &mask.as_array(),
+                parse_arc_weight(arc_weight)?,
+                watershed_line,
+            );
+            Ok(forest.into_pyarrays(py))
+        }
+    };
+}
+
+impl_arc_weight_watershed!(
+    arc_weight_watershed_from_minima_2d,
+    arc_weight_watershed_from_markers_2d,
+    2
+);
+impl_arc_weight_watershed!(
+    arc_weight_watershed_from_minima_3d,
+    arc_weight_watershed_from_markers_3d,
+    3
+);
@@ -0,0 +1,169 @@
+use ndarray::*;
+use num_traits::ToPrimitive;
+use numpy::{Element, IntoPyArray, PyArray, PyReadonlyArray};
+use pyo3::prelude::*;
+
+use crate::component_tree::ComponentTree;
+
+/// Connected filter keeping the max-tree (opening) or min-tree (closing) nodes accepted by
+/// `attribute`, removed nodes get the level of their closest kept ancestor.
+fn attribute_filter<T, D, F>(
+    image: &ArrayView<T, D>,
+    opening: bool,
+    connectivity: usize,
+    attribute: F,
+) -> Array<T, D>
+where
+    T: Copy + Element + PartialOrd + ToPrimitive,
+    D: Dimension,
+    F: Fn(&ComponentTree<T>, usize) -> bool,
+{
+    let tree = ComponentTree::new(image, opening, connectivity);
+    let keep: Vec<bool> = (0..tree.parents.len())
+        .map(|k| attribute(&tree, k))
+        .collect();
+    tree.reconstruct(&keep).into_dimensionality().unwrap()
+}
+
+/// Removes the bright components with less than `area` pixels.
+pub fn area_opening<T, D>(image: &ArrayView<T, D>, area: usize, connectivity: usize) -> Array<T, D>
+where
+    T: Copy + Element + PartialOrd + ToPrimitive,
+    D: Dimension,
+{
+    attribute_filter(image, true, connectivity, |tree, k| tree.area[k] >= area)
+}
+
+/// Removes the dark components with less than `area` pixels.
+pub fn area_closing<T, D>(image: &ArrayView<T, D>, area: usize, connectivity: usize) -> Array<T, D>
+where
+    T: Copy + Element + PartialOrd + ToPrimitive,
+    D: Dimension,
+{
+    attribute_filter(image, false, connectivity, |tree, k| tree.area[k] >= area)
+}
+
+/// Removes the bright components with volume below `volume`.
+pub fn volume_opening<T, D>(
+    image: &ArrayView<T, D>,
+    volume: f64,
+    connectivity: usize,
+) -> Array<T, D>
+where
+    T: Copy + Element + PartialOrd + ToPrimitive,
+    D: Dimension,
+{
+    attribute_filter(image, true, connectivity, |tree, k| {
+        tree.volume[k] >= volume
+    })
+}
+
+/// Removes the dark components with volume below `volume`.
+pub fn volume_closing<T, D>(
+    image: &ArrayView<T, D>,
+    volume: f64,
+    connectivity: usize,
+) -> Array<T, D>
+where
+    T: Copy + Element + PartialOrd + ToPrimitive,
+    D: Dimension,
+{
+    attribute_filter(image, false, connectivity, |tree, k| {
+        tree.volume[k] >= volume
+    })
+}
+
+#[test]
+fn test_attribute_filters() {
+    let image = array![
+        [0, 0, 0, 0, 0],
+        [0, 9, 0, 5, 5],
+        [0, 0, 0, 5, 7],
+        [3, 3, 0, 0, 0],
+    ];
+
+    assert_eq!(
+        area_opening(&image.view(), 2, 4),
+        array![
+            [0, 0, 0, 0, 0],
+            [0, 0, 0, 5, 5],
+            [0, 0, 0, 5, 5],
+            [3, 3, 0, 0, 0],
+        ]
+    );
+    // the peaks at 9 and 7 have volumes 9 and 2, the plateaus at 5 and 3 have volumes 22 and 6
+    assert_eq!(
+        volume_opening(&image.view(), 7.0, 4),
+        array![
+            [0, 0, 0, 0, 0],
+            [0, 9, 0, 5, 5],
+            [0, 0, 0, 5, 5],
+            [0, 0, 0, 0, 0],
+        ]
+    );
+
+    let inverted = image.mapv(|x| 9 - x);
+    assert_eq!(
+        area_closing(&inverted.view(), 2, 4),
+        area_opening(&image.view(), 2, 4).mapv(|x| 9 - x)
+    );
+    assert_eq!(
+        volume_closing(&inverted.view(), 10.0, 4),
+        volume_opening(&image.view(), 10.0, 4).mapv(|x| 9 - x)
+    );
+}
+
+macro_rules! impl_attribute_filters {
+    ($area_name:ident, $volume_name:ident, $ty:ty, $dim:expr) => {
+        #[pyfunction]
+        pub fn $area_name<'py>(
+            py: Python<'py>,
+            image: PyReadonlyArray<$ty, Dim<[usize; $dim]>>,
+            area: usize,
+            opening: bool,
+            connectivity: usize,
+        ) -> PyResult<&'py PyArray<$ty, Dim<[usize; $dim]>>> {
+            let arr = if opening {
+                area_opening(&image.as_array(), area, connectivity)
+            } else {
+                area_closing(&image.as_array(), area, connectivity)
+            };
+            Ok(arr.into_pyarray(py))
+        }
+
+        #[pyfunction]
+        pub fn $volume_name<'py>(
+            py: Python<'py>,
+            image: PyReadonlyArray<$ty, Dim<[usize; $dim]>>,
+            volume: f64,
+            opening: bool,
+            connectivity: usize,
+        ) -> PyResult<&'py PyArray<$ty, Dim<[usize; $dim]>>> {
+            let arr = if opening {
+                volume_opening(&image.as_array(), volume, connectivity)
+            } else {
+                volume_closing(&image.as_array(), volume, connectivity)
+            };
+            Ok(arr.into_pyarray(py))
+        }
+    };
+}
+
+impl_attribute_filters!(area_filter_u8_2d, volume_filter_u8_2d, u8, 2);
+impl_attribute_filters!(area_filter_u8_3d, volume_filter_u8_3d, u8, 3);
+impl_attribute_filters!(area_filter_u16_2d, volume_filter_u16_2d, u16, 2);
+impl_attribute_filters!(area_filter_u16_3d, volume_filter_u16_3d, u16, 3);
+impl_attribute_filters!(area_filter_u32_2d, volume_filter_u32_2d, u32, 2);
+impl_attribute_filters!(area_filter_u32_3d, volume_filter_u32_3d, u32, 3);
+
+impl_attribute_filters!(area_filter_i16_2d, volume_filter_i16_2d, i16, 2);
+impl_attribute_filters!(area_filter_i16_3d, volume_filter_i16_3d, i16, 3);
+impl_attribute_filters!(area_filter_i32_2d, volume_filter_i32_2d, i32, 2);
+impl_attribute_filters!(area_filter_i32_3d, volume_filter_i32_3d, i32, 3);
+impl_attribute_filters!(area_filter_i64_2d, volume_filter_i64_2d, i64, 2);
+impl_attribute_filters!(area_filter_i64_3d, volume_filter_i64_3d, i64, 3);
+
+impl_attribute_filters!(area_filter_f32_2d, volume_filter_f32_2d, f32, 2);
+impl_attribute_filters!(area_filter_f32_3d, volume_filter_f32_3d, f32, 3);
+impl_attribute_filters!(area_filter_f64_2d, volume_filter_f64_2d, f64, 2);
+impl_attribute_filters!(area_filter_f64_3d, volume_filter_f64_3d, f64, 3);
@@ -1,4 +1,4 @@
-use crate::priority_queue::{ElemStatus, PriorityQueue};
+use crate::priority_queue::{ElemStatus, Policy, PriorityQueue};
 use ndarray::prelude::Array1;
 use ndarray_stats::QuantileExt;
 use num_traits::ToPrimitive;
@@ -17,6 +17,7 @@ pub struct BucketQueue<'a, T: 'static> {
     min_priority: usize,
     max_priority: usize,
     pub status: Vec<ElemStatus>,
+    policy: Policy,
 }
 
 impl<'a, T: 'static> PriorityQueue<'a, T> for BucketQueue<'a, T>
@@ -29,14 +30,15 @@ where
 
     fn is_empty(&mut self) -> bool {
         while self.min_priority <= self.max_priority {
-            let bucket = &mut self.buckets[self.min_priority];
+            let current = self.current_priority();
+            let bucket = &mut self.buckets[current];
             while !bucket.is_empty() {
                 if self.status[bucket[0]] == ElemStatus::IN {
                     return false;
                 }
                 bucket.pop_front();
             }
-            self.min_priority += 1;
+            self.advance_priority();
         }
         true
     }
@@ -68,7 +70,8 @@ where
         if self.is_empty() {
             return Err("Queue is empty");
         }
-        let index = self.buckets[self.min_priority].pop_front().unwrap();
+        let current = self.current_priority();
+        let index = self.buckets[current].pop_front().unwrap();
         self.status[index] = ElemStatus::POPPED;
         Ok(index)
     }
@@ -91,8 +94,12 @@ where
         if self.status[index] != ElemStatus::IN {
             self.values[index] = value;
         } else {
-            // if value is smaller than current it doesn't need to be removed
-            if value > self.values[index] {
+            // if value has higher priority than current it doesn't need to be removed
+            let lower_priority = match self.policy {
+                Policy::Min => value > self.values[index],
+                Policy::Max => value < self.values[index],
+            };
+            if lower_priority {
                 self.remove(index).unwrap();
             }
             self.values[index] = value;
@@ -127,6 +134,10 @@ where
     T: Sub<Output = T> + ToPrimitive + Copy + PartialOrd,
 {
     pub fn new(values: &'a mut Array1<T>) -> Self {
+        BucketQueue::with_policy(values, Policy::Min)
+    }
+
+    pub fn with_policy(values: &'a mut Array1<T>, policy: Policy) -> Self {
         let size = values.len();
         if size < 1 {
             panic!("Heap size must be greater than 0");
@@ -141,6 +152,25 @@ where
             min_priority: n_buckets,
             max_priority: 0,
             status,
+            policy,
+        }
+    }
+
+    #[inline(always)]
+    fn current_priority(&self) -> usize {
+        match self.policy {
+            Policy::Min => self.min_priority,
+            Policy::Max => self.max_priority,
+        }
+    }
+
+    // moves current priority towards the other end of the queue, it's invalid once it crosses it
+    #[inline(always)]
+    fn advance_priority(&mut self) {
+        match self.policy {
+            Policy::Min => self.min_priority += 1,
+            Policy::Max if self.max_priority == 0 => self.min_priority += 1,
+            Policy::Max => self.max_priority -= 1,
         }
     }
 
@@ -220,3 +250,28 @@ fn test_bucket_queue() {
         assert_eq!(queue.status[i], ElemStatus::OUT);
     }
 }
+
+#[test]
+fn test_bucket_queue_max_policy() {
+    let mut values = Array1::from(vec![3, 1, 2, 0]);
+    let mut queue = BucketQueue::with_policy(&mut values, Policy::Max);
+
+    for i in 0..4 {
+        queue.insert(i, -1).unwrap();
+    }
+
+    assert_eq!(queue.pop().unwrap(), 0);
+
+    queue.update_value(1, 5, -1);
+    assert_eq!(queue.pop().unwrap(), 1);
+
+    queue.update_value(2, 0, -1);
+    assert_eq!(queue.pop().unwrap(), 3);
+    assert_eq!(queue.pop().unwrap(), 2);
+    assert!(queue.is_empty());
+
+    queue.insert(1, -1).unwrap();
+    assert!(!queue.is_empty());
+    assert_eq!(queue.pop().unwrap(), 1);
+    assert!(queue.is_empty());
+}
@@ -0,0 +1,299 @@
+// pyo3 0.19 #[pymethods] expansion defines impls inside functions
+#![allow(non_local_definitions)]
+
+use ndarray::*;
+use num_traits::ToPrimitive;
+use numpy::{
+    Element, IntoPyArray, PyArray1, PyArray2, PyArrayDyn, PyReadonlyArray1, PyReadonlyArrayDyn,
+};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::adjacency::grid_adjacency_with_connectivity;
+
+/// Max-tree (or min-tree) of the connected components of the upper (lower) level sets.
+/// Nodes are ordered from the root, so parents always precede their children.
+pub struct ComponentTree<T> {
+    shape: Vec<usize>,
+    /// parent of each node, the root is the first node and its own parent
+    pub parents: Vec<usize>,
+    pub levels: Vec<T>,
+    /// node of each pixel
+    pub nodes: Array1<usize>,
+    pub area: Vec<usize>,
+    /// sum of the distances of the node pixels to the parent level
+    pub volume: Vec<f64>,
+    /// distance between the extremal level of the node and the parent level
+    pub height: Vec<f64>,
+    /// minimum coordinates followed by the maximum coordinates of each node
+    pub bbox: Array2<usize>,
+}
+
+/// Root of `p` in the union-find `zpar`, compressing the path on the way.
+pub(crate) fn find_root(zpar: &mut [usize], p: usize) -> usize {
+    let mut root = p;
+    while zpar[root] != root {
+        root = zpar[root];
+    }
+    let mut p = p;
+    while zpar[p] != root {
+        let next = zpar[p];
+        zpar[p] = root;
+        p = next;
+    }
+    root
+}
+
+impl<T> ComponentTree<T>
+where
+    T: Copy + Element + PartialOrd + ToPrimitive,
+{
+    pub fn new<D: Dimension>(image: &ArrayView<T, D>, max_tree: bool, connectivity: usize) -> Self {
+        let shape = image.shape().to_vec();
+        let adj = grid_adjacency_with_connectivity(&shape, connectivity);
+
+        let image = image.to_shape(image.len()).unwrap();
+        let size = image.len();
+
+        let mut order: Vec<usize> = (0..size).collect();
+        if max_tree {
+            order.sort_by(|&a, &b| {
+                image[b]
+                    .partial_cmp(&image[a])
+                    .expect("Image must not contain NaN")
+            });
+        } else {
+            order.sort_by(|&a, &b| {
+                image[a]
+                    .partial_cmp(&image[b])
+                    .expect("Image must not contain NaN")
+            });
+        }
+
+        // union-find, usize::MAX marks the pixels not processed yet
+        let mut parent = vec![0; size];
+        let mut zpar = vec![usize::MAX; size];
+        for &p in &order {
+            parent[p] = p;
+            zpar[p] = p;
+            for q in adj.neighbors(p) {
+                if zpar[q] == usize::MAX {
+                    continue;
+                }
+                let r = find_root(&mut zpar, q);
+                if r != p {
+                    parent[r] = p;
+                    zpar[r] = p;
+                }
+            }
+        }
+
+        // parents are processed after their children, so the reverse order visits them first
+        for &p in order.iter().rev() {
+            let q = parent[p];
+            if image[parent[q]] == image[q] {
+                parent[p] = parent[q];
+            }
+        }
+
+        let mut nodes = Array1::from_elem(size, 0);
+        let mut parents = Vec::new();
+        let mut levels = Vec::new();
+        for &p in order.iter().rev() {
+            let q = parent[p];
+            if q == p || image[q] != image[p] {
+                nodes[p] = parents.len();
+                parents.push(if q == p { nodes[p] } else { nodes[q] });
+                levels.push(image[p]);
+            } else {
+                nodes[p] = nodes[q];
+            }
+        }
+
+        let n_nodes = parents.len();
+        let ndim = shape.len();
+        let mut area = vec![0; n_nodes];
+        let mut bbox = Array2::zeros((n_nodes, 2 * ndim));
+        bbox.slice_mut(s![.., ..ndim]).fill(usize::MAX);
+
+        for p in 0..size {
+            let k = nodes[p];
+            area[k] += 1;
+            let mut index = p;
+            for d in (0..ndim).rev() {
+                let coord = index % shape[d];
+                index /= shape[d];
+                bbox[[k, d]] = bbox[[k, d]].min(coord);
+                bbox[[k, ndim + d]] = bbox[[k, ndim + d]].max(coord);
+            }
+        }
+
+        let level = |k: usize| levels[k].to_f64().unwrap();
+        let mut extremal: Vec<f64> = (0..n_nodes).map(level).collect();
+        let mut volume = vec![0.0; n_nodes];
+        let mut height = vec![0.0; n_nodes];
+
+        // children are visited before their parents
+        for k in (0..n_nodes).rev() {
+            let par = parents[k];
+            volume[k] += area[k] as f64 * (level(k) - level(par)).abs();
+            height[k] = (extremal[k] - level(par)).abs();
+            if k == par {
+                continue;
+            }
+            area[par] += area[k];
+            volume[par] += volume[k];
+            extremal[par] = if max_tree {
+                extremal[par].max(extremal[k])
+            } else {
+                extremal[par].min(extremal[k])
+            };
+            for d in 0..ndim {
+                bbox[[par, d]] = bbox[[par, d]].min(bbox[[k, d]]);
+                bbox[[par, ndim + d]] = bbox[[par, ndim + d]].max(bbox[[k, ndim + d]]);
+            }
+        }
+
+        ComponentTree {
+            shape,
+            parents,
+            levels,
+            nodes,
+            area,
+            volume,
+            height,
+            bbox,
+        }
+    }
+
+    /// Image where the pixels of the removed nodes get the level of their closest kept ancestor,
+    /// the root is always kept.
+    pub fn reconstruct(&self, keep: &[bool]) -> ArrayD<T> {
+        if keep.len() != self.parents.len() {
+            panic!("`keep` must have one value per node");
+        }
+        let mut levels = self.levels.clone();
+        for k in 1..levels.len() {
+            if !keep[k] {
+                levels[k] = levels[self.parents[k]];
+            }
+        }
+        self.nodes
+            .mapv(|k| levels[k])
+            .into_shape(IxDyn(&self.shape))
+            .unwrap()
+    }
+
+    pub fn node_map(&self) -> ArrayD<usize> {
+        self.nodes.to_shape(IxDyn(&self.shape)).unwrap().to_owned()
+    }
+}
+
+#[test]
+fn test_component_tree() {
+    let image = array![[1, 3, 2, 3, 0]];
+
+    let tree = ComponentTree::new(&image.view(), true, 4);
+    assert_eq!(tree.parents, vec![0, 0, 1, 2, 2]);
+    assert_eq!(tree.levels, vec![0, 1, 2, 3, 3]);
+    assert_eq!(tree.nodes, array![1, 4, 2, 3, 0]);
+    assert_eq!(tree.area, vec![5, 4, 3, 1, 1]);
+    assert_eq!(tree.volume, vec![9.0, 9.0, 5.0, 1.0, 1.0]);
+    assert_eq!(tree.height, vec![3.0, 3.0, 2.0, 1.0, 1.0]);
+    assert_eq!(tree.bbox.row(2), array![0, 1, 0, 3]);
+
+    let keep: Vec<bool> = tree.area.iter().map(|&a| a >= 2).collect();
+    assert_eq!(tree.reconstruct(&keep), array![[1, 2, 2, 2, 0]].into_dyn());
+
+    let tree = ComponentTree::new(&image.view(), false, 4);
+    assert_eq!(tree.parents.len(), 4);
+    let keep: Vec<bool> = tree.area.iter().map(|&a| a >= 2).collect();
+    assert_eq!(
+        tree.reconstruct(&keep),
+        Array2::from_elem((1, 5), 3).into_dyn()
+    );
+}
+
+#[test]
+#[should_panic(expected = "NaN")]
+fn test_component_tree_nan() {
+    ComponentTree::new(&array![[1.0, f64::NAN, 2.0]].view(), true, 4);
+}
+
+macro_rules! impl_component_tree {
+    ($new_name:ident, $ty:ty) => {
+        #[pyclass]
+        pub struct $new_name {
+            tree: ComponentTree<$ty>,
+        }
+
+        #[pymethods]
+        impl $new_name {
+            #[new]
+            fn new(
+                image: PyReadonlyArrayDyn<$ty>,
+                max_tree: bool,
+                connectivity: usize,
+            ) -> PyResult<Self> {
+                if image.ndim() != 2 && image.ndim() != 3 {
+                    return Err(PyValueError::new_err("Image must be 2 or 3-D."));
+                }
+                Ok($new_name {
+                    tree: ComponentTree::new(&image.as_array(), max_tree, connectivity),
+                })
+            }
+
+            fn parents<'py>(&self, py: Python<'py>) -> &'py PyArray1<usize> {
+                PyArray1::from_slice(py, &self.tree.parents)
+            }
+
+            fn levels<'py>(&self, py: Python<'py>) -> &'py PyArray1<$ty> {
+                PyArray1::from_slice(py, &self.tree.levels)
+            }
+
+            fn node_map<'py>(&self, py: Python<'py>) -> &'py PyArrayDyn<usize> {
+                self.tree.node_map().into_pyarray(py)
+            }
+
+            fn area<'py>(&self, py: Python<'py>) -> &'py PyArray1<usize> {
+                PyArray1::from_slice(py, &self.tree.area)
+            }
+
+            fn volume<'py>(&self, py: Python<'py>) -> &'py PyArray1<f64> {
+                PyArray1::from_slice(py, &self.tree.volume)
+            }
+
+            fn height<'py>(&self, py: Python<'py>) -> &'py PyArray1<f64> {
+                PyArray1::from_slice(py, &self.tree.height)
+            }
+
+            fn bbox<'py>(&self, py: Python<'py>) -> &'py PyArray2<usize> {
+                self.tree.bbox.clone().into_pyarray(py)
+            }
+
+            fn reconstruct<'py>(
+                &self,
+                py: Python<'py>,
+                keep: PyReadonlyArray1<bool>,
+            ) -> PyResult<&'py PyArrayDyn<$ty>> {
+                if keep.len() != self.tree.parents.len() {
+                    return Err(PyValueError::new_err(
+                        "`keep` must have one value per node.",
+                    ));
+                }
+                Ok(self.tree.reconstruct(keep.as_slice()?).into_pyarray(py))
+            }
+        }
+    };
+}
+
+impl_component_tree!(ComponentTreeU8, u8);
+impl_component_tree!(ComponentTreeU16, u16);
+impl_component_tree!(ComponentTreeU32, u32);
+
+impl_component_tree!(ComponentTreeI16, i16);
+impl_component_tree!(ComponentTreeI32, i32);
+impl_component_tree!(ComponentTreeI64, i64);
+
+impl_component_tree!(ComponentTreeF32, f32);
+impl_component_tree!(ComponentTreeF64, f64);
@@ -0,0 +1,368 @@
+// pyo3 0.19 #[pymethods] expansion defines impls inside functions
+#![allow(non_local_definitions)]
+
+use ndarray::*;
+use num_traits::Bounded;
+use numpy::{Element, IntoPyArray, PyArrayDyn, PyReadonlyArrayDyn};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashSet, VecDeque};
+
+use crate::adjacency::{Adjacency, AdjacencyGrid2D, AdjacencyGrid3D};
+use crate::ift::Seed;
+
+/// Pixel waiting in the DIFT queue, the lowest cost pops first and ties pop in insertion order.
+struct QueueEntry<T> {
+    cost: T,
+    order: usize,
+    index: usize,
+}
+
+impl<T: PartialOrd> Ord for QueueEntry<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // reversed, the binary heap pops the largest entry
+        other
+            .cost
+            .partial_cmp(&self.cost)
+            .unwrap_or(Ordering::Equal)
+            .then(other.order.cmp(&self.order))
+    }
+}
+
+impl<T: PartialOrd> PartialOrd for QueueEntry<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: PartialOrd> PartialEq for QueueEntry<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T: PartialOrd> Eq for QueueEntry<T> {}
+
+/// Differential IFT (DIFT) of the watershed (fmax) on `topology`.
+/// The forest is kept between calls, so adding or removing seeds only recomputes the affected trees.
+pub struct DifferentialIFT<T> {
+    shape: Vec<usize>,
+    adj: Box<dyn Adjacency + Send>,
+    topology: Array1<T>,
+    mask: Array1<bool>,
+    cost: Array1<T>,
+    pred: Array1<i64>,
+    root: Array1<i64>,
+    label: Array1<usize>,
+    /// pixels to process, entries whose cost is outdated are skipped when popped
+    queue: BinaryHeap<QueueEntry<T>>,
+    n_pushed: usize,
+    /// run in which each pixel was last popped, updated pixels are cleared to be processed again
+    popped_in: Vec<usize>,
+    n_runs: usize,
+}
+
+impl<T> DifferentialIFT<T>
+where
+    T: Bounded + Copy + Element + PartialOrd + 'static,
+{
+    pub fn new<D: Dimension>(topology: &ArrayView<T, D>, mask: &ArrayView<bool, D>) -> Self {
+        let adj: Box<dyn Adjacency + Send> = match topology.ndim() {
+            2 => Box::new(AdjacencyGrid2D::new(topology.shape())),
+            3 => Box::new(AdjacencyGrid3D::new(topology.shape())),
+            _ => panic!("Unsupported dimension!"),
+        };
+        if topology.shape() != mask.shape() {
+            panic!("Topology and mask must have the same shape");
+        }
+        let size = topology.len();
+        DifferentialIFT {
+            shape: topology.shape().to_vec(),
+            adj,
+            topology: topology.to_shape(size).unwrap().to_owned(),
+            mask: mask.to_shape(size).unwrap().to_owned(),
+            cost: Array1::from_elem(size, T::max_value()),
+            pred: Array1::from_elem(size, -1),
+            root: Array1::from_elem(size, -1),
+            label: Array1::zeros(size),
+            queue: BinaryHeap::new(),
+            n_pushed: 0,
+            popped_in: vec![0; size],
+            n_runs: 0,
+        }
+    }
+
+    /// Adds seeds with the given `labels` at the flat `indices`, seeds on existing seeds replace them.
+    pub fn add_seeds(&mut self, indices: &[usize], labels: &[usize]) {
+        if indices.len() != labels.len() {
+            panic!("Indices and labels must have the same length");
+        }
+        self.check_indices(indices);
+        let seeds: Vec<Seed<T>> = indices
+            .iter()
+            .zip(labels)
+            .map(|(&i, &l)| Seed::new(i, self.topology[i], l))
+            .collect();
+        self.run(&seeds, &[]);
+    }
+
+    /// Removes the seeds at the flat `indices` and their trees, indices that are not seeds are ignored.
+    pub fn remove_seeds(&mut self, indices: &[usize]) {
+        self.check_indices(indices);
+        self.run(&[], indices);
+    }
+
+    fn check_indices(&self, indices: &[usize]) {
+        if indices.iter().any(|&i| i >= self.label.len()) {
+            panic!("Indices must be smaller than the number of pixels");
+        }
+    }
+
+    pub fn labels(&self) -> ArrayD<usize> {
+        self.label.to_shape(IxDyn(&self.shape)).unwrap().to_owned()
+    }
+
+    pub fn costs(&self) -> ArrayD<T> {
+        self.cost.to_shape(IxDyn(&self.shape)).unwrap().to_owned()
+    }
+
+    fn push(&mut self, index: usize) {
+        self.queue.push(QueueEntry {
+            cost: self.cost[index],
+            order: self.n_pushed,
+            index,
+        });
+        self.n_pushed += 1;
+    }
+
+    pub fn run(&mut self, seeds: &[Seed<T>], removed: &[usize]) {
+        self.n_runs += 1;
+        for p in self.remove_trees(removed) {
+            self.push(p);
+        }
+
+        for seed in seeds {
+            if !self.mask[seed.index] {
+                continue;
+            }
+            self.cost[seed.index] = seed.cost;
+            self.pred[seed.index] = -1;
+            self.root[seed.index] = seed.index as i64;
+            self.label[seed.index] = seed.label;
+            self.popped_in[seed.index] = 0;
+            self.push(seed.index);
+        }
+
+        while let Some(QueueEntry { cost, index: p, .. }) = self.queue.pop() {
+            if self.popped_in[p] == self.n_runs || cost != self.cost[p] {
+                continue;
+            }
+            self.popped_in[p] = self.n_runs;
+            let cost_p = self.cost[p];
+
+            for q in self.adj.neighbors(p) {
+                if !self.mask[q] {
+                    continue;
+                }
+                // pixels that are not reached have no cost to compare with
+                let reached = self.root[q] >= 0;
+                // nodes conquered by `p` must be updated even when their cost increases
+                let is_child = self.pred[q] == p as i64;
+                if !reached || self.cost[q] > cost_p || is_child {
+                    // fmax
+                    let topology_q = self.topology[q];
+                    let path_cost = if topology_q > cost_p {
+                        topology_q
+                    } else {
+                        cost_p
+                    };
+
+                    if !reached || path_cost < self.cost[q] || is_child {
+                        self.pred[q] = p as i64;
+                        self.root[q] = self.root[p];
+                        self.label[q] = self.label[p];
+                        self.cost[q] = path_cost;
+                        self.popped_in[q] = 0;
+                        self.push(q);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Resets the trees rooted at `removed` and returns the nodes of the remaining trees on their frontier.
+    fn remove_trees(&mut self, removed: &[usize]) -> Vec<usize> {
+        let mut removed_roots = HashSet::new();
+        let mut fifo = VecDeque::new();
+
+        for &r in removed {
+            if self.root[r] == r as i64 && removed_roots.insert(r) {
+                fifo.push_back(r);
+            }
+        }
+
+        for &r in &fifo {
+            self.cost[r] = T::max_value();
+            self.pred[r] = -1;
+            self.root[r] = -1;
+            self.label[r] = 0;
+        }
+
+        let mut frontier = Vec::new();
+        while let Some(p) = fifo.pop_front() {
+            for q in self.adj.neighbors(p) {
+                if !self.mask[q] {
+                    continue;
+                }
+                if self.pred[q] == p as i64 {
+                    self.cost[q] = T::max_value();
+                    self.pred[q] = -1;
+                    self.root[q] = -1;
+                    self.label[q] = 0;
+                    fifo.push_back(q);
+                } else if self.root[q] >= 0 && !removed_roots.contains(&(self.root[q] as usize)) {
+                    frontier.push(q);
+                }
+            }
+        }
+
+        frontier
+    }
+}
+
+#[test]
+fn test_differential_ift() {
+    use crate::watershed::watershed_from_markers;
+
+    let image = array![
+        [1, 2, 3, 9, 1, 1],
+        [2, 8, 3, 9, 2, 1],
+        [3, 3, 2, 7, 4, 3],
+        [5, 6, 1, 8, 5, 2],
+    ];
+    let mask = Array2::from_elem(image.dim(), true);
+    let mut dift = DifferentialIFT::new(&image.view(), &mask.view());
+
+    let mut markers = Array2::<usize>::zeros(image.dim());
+    markers[[0, 0]] = 1;
+    markers[[0, 5]] = 2;
+    markers[[3, 2]] = 3;
+    dift.add_seeds(&[0, 5, 20], &[1, 2, 3]);
+
+    let forest = watershed_from_markers(&image.view(), &markers.view(), &mask.view(), false, false);
+    assert_eq!(dift.costs(), forest.costs.into_dyn());
+    assert_eq!(dift.labels(), forest.labels.into_dyn());
+
+    markers[[3, 2]] = 0;
+    markers[[2, 4]] = 4;
+    dift.remove_seeds(&[20]);
+    dift.add_seeds(&[16], &[4]);
+
+    let forest = watershed_from_markers(&image.view(), &markers.view(), &mask.view(), false, false);
+    assert_eq!(dift.costs(), forest.costs.into_dyn());
+    assert_eq!(dift.labels(), forest.labels.into_dyn());
+
+    // a new seed inside an existing tree takes over its subtree, ties may be broken differently
+    markers[[1, 0]] = 5;
+    dift.add_seeds(&[6], &[5]);
+    let forest = watershed_from_markers(&image.view(), &markers.view(), &mask.view(), false, false);
+    assert_eq!(dift.costs(), forest.costs.into_dyn());
+    let labels = dift.labels();
+    assert_eq!(labels[[0, 0]], 1);
+    assert!([[1, 0], [2, 0], [3, 0]].iter().all(|&p| labels[p] == 5));
+
+    dift.remove_seeds(&[0, 5, 6, 16]);
+    assert_eq!(dift.labels(), ArrayD::zeros(IxDyn(&[4, 6])));
+
+    // plateaus at the largest value of the dtype are conquered too
+    let image = array![[0u8, 255, 10], [0, 255, 10]];
+    let mask = Array2::from_elem(image.dim(), true);
+    let mut dift = DifferentialIFT::new(&image.view(), &mask.view());
+    dift.add_seeds(&[0, 5], &[1, 2]);
+    assert_eq!(dift.labels(), array![[1, 1, 2], [1, 1, 2]].into_dyn());
+    dift.remove_seeds(&[0]);
+    assert_eq!(dift.labels(), Array2::from_elem((2, 3), 2).into_dyn());
+}
+
+#[test]
+#[should_panic]
+fn test_differential_ift_seed_labels() {
+    let image = array![[1, 2, 3]];
+    let mask = Array2::from_elem(image.dim(), true);
+    DifferentialIFT::new(&image.view(), &mask.view()).add_seeds(&[0, 2], &[1]);
+}
+
+fn check_seed_indices(indices: &[usize], size: usize) -> PyResult<()> {
+    match indices.iter().find(|&&i| i >= size) {
+        Some(i) => Err(PyValueError::new_err(format!(
+            "Index {i} is out of bounds for {size} pixels."
+        ))),
+        None => Ok(()),
+    }
+}
+
+macro_rules! impl_differential_watershed {
+    ($new_name:ident, $ty:ty) => {
+        #[pyclass]
+        pub struct $new_name {
+            dift: DifferentialIFT<$ty>,
+        }
+
+        #[pymethods]
+        impl $new_name {
+            #[new]
+            fn new(
+                topology: PyReadonlyArrayDyn<$ty>,
+                mask: PyReadonlyArrayDyn<bool>,
+            ) -> PyResult<Self> {
+                if topology.shape() != mask.shape() {
+                    return Err(PyValueError::new_err(
+                        "Mask and topology must have the same shape.",
+                    ));
+                }
+                Ok($new_name {
+                    dift: DifferentialIFT::new(&topology.as_array(), &mask.as_array()),
+                })
+            }
+
+            fn add_seeds(&mut self, indices: Vec<usize>, labels: Vec<usize>) -> PyResult<()> {
+                if indices.len() != labels.len() {
+                    return Err(PyValueError::new_err(format!(
+                        "Expected {} labels. Found {} instead.",
+                        indices.len(),
+                        labels.len()
+                    )));
+                }
+                check_seed_indices(&indices, self.dift.label.len())?;
+                self.dift.add_seeds(&indices, &labels);
+                Ok(())
+            }
+
+            fn remove_seeds(&mut self, indices: Vec<usize>) -> PyResult<()> {
+                check_seed_indices(&indices, self.dift.label.len())?;
+                self.dift.remove_seeds(&indices);
+                Ok(())
+            }
+
+            fn labels<'py>(&self, py: Python<'py>) -> &'py PyArrayDyn<usize> {
+                self.dift.labels().into_pyarray(py)
+            }
+
+            fn costs<'py>(&self, py: Python<'py>) -> &'py PyArrayDyn<$ty> {
+                self.dift.costs().into_pyarray(py)
+            }
+        }
+    };
+}
+
+impl_differential_watershed!(DifferentialWatershedU8, u8);
+impl_differential_watershed!(DifferentialWatershedU16, u16);
+impl_differential_watershed!(DifferentialWatershedU32, u32);
+
+impl_differential_watershed!(DifferentialWatershedI16, i16);
+impl_differential_watershed!(DifferentialWatershedI32, i32);
+impl_differential_watershed!(DifferentialWatershedI64, i64);
+
+impl_differential_watershed!(DifferentialWatershedF32, f32);
+impl_differential_watershed!(DifferentialWatershedF64, f64);
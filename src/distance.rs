@@ -0,0 +1,377 @@
+use ndarray::*;
+use numpy::{IntoPyArray, PyArray, PyReadonlyArray};
+use pyo3::prelude::*;
+
+use crate::adjacency::{grid_adjacency_with_connectivity, squared_distance};
+use crate::ift::{image_foresting_transform, FEuclidean, FSum, Seed};
+
+/// Euclidean distance from each `true` pixel to the closest `false` pixel and the flat index of this
+/// closest pixel (feature transform). Distances are infinite when the image has no `false` pixel.
+/// Isotropic spacings use the IFT with the feuc path cost on the full grid adjacency, which is not
+/// exact for anisotropic ones, so those use a separable transform instead.
+pub fn euclidean_distance_transform<D>(
+    image: &ArrayView<bool, D>,
+    spacing: &[f64],
+) -> (Array<f64, D>, Array<i64, D>)
+where
+    D: Dimension,
+{
+    if spacing.len() != image.ndim() {
+        panic!("Spacing must have one value per dimension");
+    }
+    if spacing.iter().any(|&s| s <= 0.0) {
+        panic!("Spacing must be positive");
+    }
+
+    if spacing.iter().all(|&s| s == spacing[0]) {
+        ift_euclidean_distance_transform(image, spacing)
+    } else {
+        separable_euclidean_distance_transform(image, spacing)
+    }
+}
+
+fn ift_euclidean_distance_transform<D>(
+    image: &ArrayView<bool, D>,
+    spacing: &[f64],
+) -> (Array<f64, D>, Array<i64, D>)
+where
+    D: Dimension,
+{
+    let shape = image.raw_dim();
+    // full connectivity, distances are not exact for direct neighbors only
+    let adj = grid_adjacency_with_connectivity(image.shape(), 3usize.pow(image.ndim() as u32) - 1);
+    let mut path_cost = FEuclidean::new(image.shape(), spacing);
+
+    let image = image.to_shape(image.len()).unwrap();
+    let mask = Array1::from_elem(image.len(), true);
+
+    let seeds: Vec<Seed<f64>> = (0..image.len())
+        .filter(|&i| !image[i])
+        .map(|i| Seed::new(i, 0.0, 1))
+        .collect();
+
+    let forest = image_foresting_transform(adj.as_ref(), &mut path_cost, &seeds, &mask.view());
+
+    let distances = forest.costs.mapv(|c| {
+        if c == f64::MAX {
+            f64::INFINITY
+        } else {
+            c.sqrt()
+        }
+    });
+
+    (
+        distances.into_shape(shape.clone()).unwrap(),
+        forest.roots.into_shape(shape).unwrap(),
+    )
+}
+
+/// Exact for any spacing, it computes the lower envelope of parabolas one axis at a time
+/// (Felzenszwalb and Huttenlocher).
+fn separable_euclidean_distance_transform<D>(
+    image: &ArrayView<bool, D>,
+    spacing: &[f64],
+) -> (Array<f64, D>, Array<i64, D>)
+where
+    D: Dimension,
+{
+    let shape = image.raw_dim();
+    let size = image.len();
+    let image = image.to_shape(size).unwrap();
+
+    let mut sq_dists: Vec<f64> = image
+        .iter()
+        .map(|&x| if x { f64::INFINITY } else { 0.0 })
+        .collect();
+    let mut features: Vec<i64> = (0..size)
+        .map(|i| if image[i] { -1 } else { i as i64 })
+        .collect();
+
+    let mut stride = size;
+    for (&length, &s) in shape.slice().iter().zip(spacing) {
+        stride /= length.max(1);
+        for start in (0..size).filter(|&i| (i / stride).is_multiple_of(length)) {
+            let line: Vec<usize> = (0..length).map(|k| start + k * stride).collect();
+            let (line_dists, line_features) = lower_envelope(&line, &sq_dists, &features, s * s);
+            for (k, &p) in line.iter().enumerate() {
+                sq_dists[p] = line_dists[k];
+                features[p] = line_features[k];
+            }
+        }
+    }
+
+    let distances = Array::from_shape_vec(size, sq_dists)
+        .unwrap()
+        .mapv(f64::sqrt);
+    (
+        distances.into_shape(shape.clone()).unwrap(),
+        Array::from_shape_vec(size, features)
+            .unwrap()
+            .into_shape(shape)
+            .unwrap(),
+    )
+}
+
+/// Minimum over the `line` positions q of f(q) + w (x - q)² for each position x, with f the
+/// squared distances, and the feature of the minimizing position.
+fn lower_envelope(
+    line: &[usize],
+    sq_dists: &[f64],
+    features: &[i64],
+    w: f64,
+) -> (Vec<f64>, Vec<i64>) {
+    let f = |k: usize| sq_dists[line[k]];
+    // abscissa where the parabolas of positions a < b intersect
+    let intersection = |a: usize, b: usize| {
+        ((f(b) + w * (b * b) as f64) - (f(a) + w * (a * a) as f64)) / (2.0 * w * (b - a) as f64)
+    };
+
+    // parabolas of the envelope and the abscissa where each one starts being the lowest
+    let mut parabolas: Vec<usize> = Vec::with_capacity(line.len());
+    let mut starts: Vec<f64> = Vec::with_capacity(line.len());
+    for q in (0..line.len()).filter(|&q| f(q).is_finite()) {
+        let mut start = f64::NEG_INFINITY;
+        while let Some(&v) = parabolas.last() {
+            start = intersection(v, q);
+            if start > *starts.last().unwrap() {
+                break;
+            }
+            parabolas.pop();
+            starts.pop();
+            start = f64::NEG_INFINITY;
+        }
+        parabolas.push(q);
+        starts.push(start);
+    }
+
+    if parabolas.is_empty() {
+        return (vec![f64::INFINITY; line.len()], vec![-1; line.len()]);
+    }
+
+    let mut k = 0;
+    (0..line.len())
+        .map(|x| {
+            while k + 1 < parabolas.len() && starts[k + 1] <= x as f64 {
+                k += 1;
+            }
+            let q = parabolas[k];
+            let dx = x as f64 - q as f64;
+            (f(q) + w * dx * dx, features[line[q]])
+        })
+        .unzip()
+}
+
+#[test]
+fn test_euclidean_distance_transform() {
+    use crate::adjacency::squared_distance;
+
+    // pseudo-random sparse background
+    let mut state: u64 = 42;
+    let mut random = || {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (state >> 33) % 100
+    };
+
+    let image = Array2::from_shape_simple_fn((32, 41), || random() > 3);
+    let sparse = Array2::from_shape_simple_fn((40, 37), || random() > 0);
+    let image3d = Array3::from_shape_simple_fn((9, 12, 11), || random() > 2);
+
+    for (image, spacing) in [
+        (image.into_dyn(), vec![1.0, 1.0]),
+        (sparse.clone().into_dyn(), vec![2.0, 2.0]),
+        (sparse.into_dyn(), vec![3.0, 1.0]),
+        (image3d.clone().into_dyn(), vec![1.0, 1.0, 1.0]),
+        (image3d.into_dyn(), vec![2.5, 1.0, 1.0]),
+    ] {
+        let (distances, features) = euclidean_distance_transform(&image.view(), &spacing);
+
+        // both transforms agree wherever the IFT is used
+        if spacing.iter().all(|&s| s == spacing[0]) {
+            let (separable, _) = separable_euclidean_distance_transform(&image.view(), &spacing);
+            assert!(distances
+                .iter()
+                .zip(&separable)
+                .all(|(a, b)| (a - b).abs() < 1e-9));
+        }
+
+        let flat_image = image.as_slice().unwrap();
+        let flat_distances = distances.as_slice().unwrap();
+        let flat_features = features.as_slice().unwrap();
+
+        for p in 0..image.len() {
+            let expected = (0..image.len())
+                .filter(|&q| !flat_image[q])
+                .map(|q| squared_distance(image.shape(), &spacing, p, q))
+                .fold(f64::MAX, f64::min)
+                .sqrt();
+            let feature_dist =
+                squared_distance(image.shape(), &spacing, p, flat_features[p] as usize).sqrt();
+            assert!(
+                (flat_distances[p] - expected).abs() < 1e-9,
+                "{} != {}",
+                flat_distances[p],
+                expected
+            );
+            assert!((feature_dist - expected).abs() < 1e-9);
+        }
+    }
+
+    // (0, 6) is at √34 from both (1, 1) and (2, 4), propagating through 8 neighbors yields 6
+    let mut image = Array2::from_elem((3, 7), true);
+    for z in [[0, 0], [1, 1], [2, 4]] {
+        image[z] = false;
+    }
+    let (distances, features) = euclidean_distance_transform(&image.view(), &[3.0, 1.0]);
+    assert_eq!(distances[[0, 6]], 34.0f64.sqrt());
+    assert!(features[[0, 6]] == 8 || features[[0, 6]] == 18);
+
+    let (distances, features) =
+        euclidean_distance_transform(&Array2::from_elem((3, 4), true).view(), &[1.0, 2.0]);
+    assert!(distances.iter().all(|&d| d == f64::INFINITY));
+    assert!(features.iter().all(|&f| f == -1));
+}
+
+/// Geodesic distance from the nonzero `markers` to every pixel, paths are restricted to `mask`.
+/// Arcs are weighted by their length under `spacing` and, when `intensity` is given, by the intensity
+/// variation along them: w(p, q) = sqrt(|p - q|² + (λ (I(q) - I(p)))²), with λ = `intensity_weight`.
+/// Returns the distances (infinite when not reachable) and the label of the closest marker.
+pub fn geodesic_distance_transform<D>(
+    markers: &ArrayView<usize, D>,
+    mask: &ArrayView<bool, D>,
+    spacing: &[f64],
+    connectivity: usize,
+    intensity: Option<&ArrayView<f64, D>>,
+    intensity_weight: f64,
+) -> (Array<f64, D>, Array<usize, D>)
+where
+    D: Dimension,
+{
+    let shape = markers.raw_dim();
+    let grid_shape = markers.shape().to_vec();
+    let adj = grid_adjacency_with_connectivity(&grid_shape, connectivity);
+
+    if spacing.len() != grid_shape.len() {
+        panic!("Spacing must have one value per dimension");
+    }
+    if spacing.iter().any(|&s| s <= 0.0) {
+        panic!("Spacing must be positive");
+    }
+
+    let markers = markers.to_shape(markers.len()).unwrap();
+    let mask = mask.to_shape(mask.len()).unwrap();
+    let intensity = intensity.map(|x| x.to_shape(x.len()).unwrap());
+
+    let seeds: Vec<Seed<f64>> = (0..markers.len())
+        .filter(|&i| markers[i] != 0)
+        .map(|i| Seed::new(i, 0.0, markers[i]))
+        .collect();
+
+    let mut path_cost = FSum::new(|p, q| {
+        let length = squared_distance(&grid_shape, spacing, p, q);
+        match &intensity {
+            Some(intensity) => {
+                let diff = intensity_weight * (intensity[q] - intensity[p]);
+                (length + diff * diff).sqrt()
+            }
+            None => length.sqrt(),
+        }
+    });
+
+    let forest = image_foresting_transform(adj.as_ref(), &mut path_cost, &seeds, &mask.view());
+
+    let distances = forest
+        .costs
+        .mapv(|c| if c == f64::MAX { f64::INFINITY } else { c });
+
+    (
+        distances.into_shape(shape.clone()).unwrap(),
+        forest.labels.into_shape(shape).unwrap(),
+    )
+}
+
+#[test]
+fn test_geodesic_distance_transform() {
+    // paths must go around the wall
+    let mut mask = Array2::from_elem((4, 5), true);
+    mask.slice_mut(s![0..3, 2]).fill(false);
+    let mut markers = Array2::<usize>::zeros((4, 5));
+    markers[[0, 0]] = 1;
+    markers[[3, 0]] = 2;
+
+    let (distances, labels) =
+        geodesic_distance_transform(&markers.view(), &mask.view(), &[1.0, 1.0], 4, None, 0.0);
+    assert_eq!(distances[[0, 4]], 7.0);
+    assert_eq!(distances[[0, 2]], f64::INFINITY);
+    assert_eq!(labels[[0, 4]], 2);
+    assert_eq!(labels[[0, 1]], 1);
+    assert_eq!(labels[[0, 2]], 0);
+
+    let (distances, _) =
+        geodesic_distance_transform(&markers.view(), &mask.view(), &[1.0, 2.0], 8, None, 0.0);
+    assert!((distances[[2, 3]] - (4.0 + 5.0f64.sqrt())).abs() < 1e-9);
+
+    let intensity = Array2::from_shape_fn((4, 5), |(_, x)| x as f64);
+    let (distances, _) = geodesic_distance_transform(
+        &markers.view(),
+        &mask.view(),
+        &[1.0, 1.0],
+        4,
+        Some(&intensity.view()),
+        1.0,
+    );
+    assert!((distances[[3, 4]] - 4.0 * 2.0f64.sqrt()).abs() < 1e-9);
+}
+
+macro_rules! impl_euclidean_distance_transform {
+    ($new_name:ident, $dim:expr) => {
+        #[pyfunction]
+        pub fn $new_name<'py>(
+            py: Python<'py>,
+            image: PyReadonlyArray<bool, Dim<[usize; $dim]>>,
+            spacing: Vec<f64>,
+        ) -> PyResult<(
+            &'py PyArray<f64, Dim<[usize; $dim]>>,
+            &'py PyArray<i64, Dim<[usize; $dim]>>,
+        )> {
+            let (distances, features) = euclidean_distance_transform(&image.as_array(), &spacing);
+            Ok((distances.into_pyarray(py), features.into_pyarray(py)))
+        }
+    };
+}
+
+impl_euclidean_distance_transform!(euclidean_distance_transform_2d, 2);
+impl_euclidean_distance_transform!(euclidean_distance_transform_3d, 3);
+
+macro_rules! impl_geodesic_distance_transform {
+    ($new_name:ident, $dim:expr) => {
+        #[pyfunction]
+        pub fn $new_name<'py>(
+            py: Python<'py>,
+            markers: PyReadonlyArray<usize, Dim<[usize; $dim]>>,
+            mask: PyReadonlyArray<bool, Dim<[usize; $dim]>>,
+            spacing: Vec<f64>,
+            connectivity: usize,
+            intensity_weight: f64,
+            intensity: Option<PyReadonlyArray<f64, Dim<[usize; $dim]>>>,
+        ) -> PyResult<(
+            &'py PyArray<f64, Dim<[usize; $dim]>>,
+            &'py PyArray<usize, Dim<[usize; $dim]>>,
+        )> {
+            let intensity = intensity.as_ref().map(|x| x.as_array());
+            let (distances, labels) = geodesic_distance_transform(
+                &markers.as_array(),
+                &mask.as_array(),
+                &spacing,
+                connectivity,
+                intensity.as_ref(),
+                intensity_weight,
+            );
+            Ok((distances.into_pyarray(py), labels.into_pyarray(py)))
+        }
+    };
+}
+
+impl_geodesic_distance_transform!(geodesic_distance_transform_2d, 2);
+impl_geodesic_distance_transform!(geodesic_distance_transform_3d, 3);
@@ -0,0 +1,140 @@
+use ndarray::*;
+use numpy::PyReadonlyArray;
+use pyo3::prelude::*;
+
+use crate::adjacency::grid_adjacency;
+use crate::forest::{OptimumPathForest, PyForest};
+use crate::ift::{image_foresting_transform, PathCost, Seed};
+
+/// fmax where the arc weight w(p, q) is the distance between the features of `q` and the mean
+/// features of the tree of `p`, the tree statistics are updated as its nodes are popped.
+pub struct DynamicTreeCost<'a> {
+    features: ArrayView2<'a, f64>,
+    // tree of each root node
+    tree: Vec<usize>,
+    sums: Array2<f64>,
+    counts: Vec<usize>,
+}
+
+impl<'a> DynamicTreeCost<'a> {
+    pub fn new(features: ArrayView2<'a, f64>, roots: &[usize]) -> Self {
+        let mut tree = vec![0; features.nrows()];
+        for (i, &r) in roots.iter().enumerate() {
+            tree[r] = i;
+        }
+        DynamicTreeCost {
+            sums: Array2::zeros((roots.len(), features.ncols())),
+            counts: vec![0; roots.len()],
+            features,
+            tree,
+        }
+    }
+
+    /// Mean features of the tree rooted at `root`.
+    pub fn mean(&self, root: usize) -> Array1<f64> {
+        let t = self.tree[root];
+        &self.sums.row(t) / self.counts[t] as f64
+    }
+}
+
+impl<'a> PathCost<f64> for DynamicTreeCost<'a> {
+    #[inline(always)]
+    fn extend(&self, _p: usize, q: usize, cost_p: f64, root: usize) -> f64 {
+        let t = self.tree[root];
+        let count = self.counts[t] as f64;
+        let weight = self
+            .features
+            .row(q)
+            .iter()
+            .zip(self.sums.row(t))
+            .map(|(&x, &s)| (x - s / count).powi(2))
+            .sum::<f64>()
+            .sqrt();
+        cost_p.max(weight)
+    }
+
+    fn on_pop(&mut self, p: usize, root: usize) {
+        let t = self.tree[root];
+        self.sums.row_mut(t).scaled_add(1.0, &self.features.row(p));
+        self.counts[t] += 1;
+    }
+}
+
+/// Watershed from `markers` with dynamic trees, `image` has the channels on its last axis.
+pub fn dynamic_tree_watershed<D>(
+    image: &ArrayView<f64, D::Larger>,
+    markers: &ArrayView<usize, D>,
+    mask: &ArrayView<bool, D>,
+) -> OptimumPathForest<f64, D>
+where
+    D: Dimension,
+{
+    let shape = markers.raw_dim();
+    let adj = grid_adjacency(markers.shape());
+
+    if image.shape()[..markers.ndim()] != *markers.shape() {
+        panic!("Image and markers must have the same spatial shape");
+    }
+
+    let size = markers.len();
+    let n_channels = image.shape()[markers.ndim()];
+    let features = image.to_shape((size, n_channels)).unwrap();
+    let markers = markers.to_shape(size).unwrap();
+    let mask = mask.to_shape(size).unwrap();
+
+    let seeds: Vec<Seed<f64>> = (0..size)
+        .filter(|&i| mask[i] && markers[i] != 0)
+        .map(|i| Seed::new(i, 0.0, markers[i]))
+        .collect();
+
+    let roots: Vec<usize> = seeds.iter().map(|s| s.index).collect();
+    let mut path_cost = DynamicTreeCost::new(features.view(), &roots);
+
+    image_foresting_transform(adj.as_ref(), &mut path_cost, &seeds, &mask.view()).into_shape(shape)
+}
+
+#[test]
+fn test_dynamic_tree_watershed() {
+    let image = Array3::from_shape_fn((3, 6, 2), |(_, x, _)| {
+        if x < 3 {
+            0.1 * x as f64
+        } else {
+            0.5 + 0.1 * x as f64
+        }
+    });
+    let mut markers = Array2::<usize>::zeros((3, 6));
+    markers[[1, 0]] = 1;
+    markers[[1, 5]] = 2;
+    let mask = Array2::from_elem((3, 6), true);
+
+    let forest = dynamic_tree_watershed(&image.view(), &markers.view(), &mask.view());
+    assert_eq!(forest.labels.column(2), array![1, 1, 1]);
+    assert_eq!(forest.labels.column(3), array![2, 2, 2]);
+    assert_eq!(forest.costs[[1, 0]], 0.0);
+    assert_eq!(forest.preds[[1, 5]], -1);
+
+    let features = array![[1.0, 0.0], [3.0, 2.0]];
+    let mut path_cost = DynamicTreeCost::new(features.view(), &[1]);
+    path_cost.on_pop(1, 1);
+    path_cost.on_pop(0, 1);
+    assert_eq!(path_cost.mean(1), array![2.0, 1.0]);
+}
+
+macro_rules! impl_dynamic_tree_watershed {
+    ($new_name:ident, $dim:expr) => {
+        #[pyfunction]
+        pub fn $new_name<'py>(
+            py: Python<'py>,
+            image: PyReadonlyArray<f64, Dim<[usize; $dim + 1]>>,
+            markers: PyReadonlyArray<usize, Dim<[usize; $dim]>>,
+            mask: PyReadonlyArray<bool, Dim<[usize; $dim]>>,
+        ) -> PyResult<PyForest<'py, f64, Dim<[usize; $dim]>>> {
+            let forest =
+                dynamic_tree_watershed(&image.as_array(), &markers.as_array(), &mask.as_array());
+            Ok(forest.into_pyarrays(py))
+        }
+    };
+}
+
+impl_dynamic_tree_watershed!(dynamic_tree_watershed_2d, 2);
+impl_dynamic_tree_watershed!(dynamic_tree_watershed_3d, 3);
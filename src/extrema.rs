@@ -0,0 +1,250 @@
+use ndarray::*;
+use num_traits::{Bounded, ToPrimitive, Zero};
+use numpy::{Element, IntoPyArray, PyArray, PyReadonlyArray};
+use pyo3::prelude::*;
+use std::collections::VecDeque;
+use std::ops::{Add, Sub};
+
+use crate::adjacency::grid_adjacency;
+use crate::reconstruction::{reconstruction, ReconstructionMethod};
+
+/// Labels the connected plateaus without any lower (minima) or higher (maxima) neighbor,
+/// labels start at 1 in raster order and the remaining pixels are 0.
+fn regional_extrema<T, D>(image: &ArrayView<T, D>, maxima: bool) -> Array<usize, D>
+where
+    T: Copy + PartialOrd,
+    D: Dimension,
+{
+    let shape = image.raw_dim();
+    let adj = grid_adjacency(image.shape());
+
+    let image = image.to_shape(image.len()).unwrap();
+    let size = image.len();
+
+    let precedes = |a: T, b: T| if maxima { a > b } else { a < b };
+
+    let mut labels = Array1::<usize>::zeros(size);
+    let mut visited = vec![false; size];
+    let mut plateau = Vec::new();
+    let mut fifo = VecDeque::new();
+    let mut n_labels = 0;
+
+    for i in 0..size {
+        if visited[i] {
+            continue;
+        }
+
+        let mut is_extremum = true;
+        plateau.clear();
+        visited[i] = true;
+        fifo.push_back(i);
+
+        while let Some(p) = fifo.pop_front() {
+            plateau.push(p);
+            for q in adj.neighbors(p) {
+                if image[q] == image[p] {
+                    if !visited[q] {
+                        visited[q] = true;
+                        fifo.push_back(q);
+                    }
+                } else if precedes(image[q], image[p]) {
+                    is_extremum = false;
+                }
+            }
+        }
+
+        if is_extremum {
+            n_labels += 1;
+            for &p in &plateau {
+                labels[p] = n_labels;
+            }
+        }
+    }
+
+    labels.into_shape(shape).unwrap()
+}
+
+pub fn regional_minima<T, D>(image: &ArrayView<T, D>) -> Array<usize, D>
+where
+    T: Copy + PartialOrd,
+    D: Dimension,
+{
+    regional_extrema(image, false)
+}
+
+pub fn regional_maxima<T, D>(image: &ArrayView<T, D>) -> Array<usize, D>
+where
+    T: Copy + PartialOrd,
+    D: Dimension,
+{
+    regional_extrema(image, true)
+}
+
+/// h-minima transform, suppresses the minima with depth up to `h` by reconstructing `image + h`
+/// over `image`.
+pub fn h_minima<T, D>(image: &ArrayView<T, D>, h: T) -> Array<T, D>
+where
+    T: Add<Output = T>
+        + Bounded
+        + Copy
+        + Element
+        + PartialOrd
+        + Sub<Output = T>
+        + ToPrimitive
+        + Zero
+        + 'static,
+    D: Dimension,
+{
+    if h < T::zero() {
+        panic!("h must be non-negative");
+    }
+    let marker = image.mapv(|x| {
+        if x > T::max_value() - h {
+            T::max_value()
+        } else {
+            x + h
+        }
+    });
+    reconstruction(&marker.view(), image, ReconstructionMethod::Erosion)
+}
+
+/// h-maxima transform, suppresses the maxima with height up to `h` by reconstructing `image - h`
+/// under `image`.
+pub fn h_maxima<T, D>(image: &ArrayView<T, D>, h: T) -> Array<T, D>
+where
+    T: Add<Output = T>
+        + Bounded
+        + Copy
+        + Element
+        + PartialOrd
+        + Sub<Output = T>
+        + ToPrimitive
+        + Zero
+        + 'static,
+    D: Dimension,
+{
+    if h < T::zero() {
+        panic!("h must be non-negative");
+    }
+    let marker = image.mapv(|x| {
+        if x < T::min_value() + h {
+            T::min_value()
+        } else {
+            x - h
+        }
+    });
+    reconstruction(&marker.view(), image, ReconstructionMethod::Dilation)
+}
+
+/// Labeled regional minima of the h-minima transform.
+pub fn extended_minima<T, D>(image: &ArrayView<T, D>, h: T) -> Array<usize, D>
+where
+    T: Add<Output = T>
+        + Bounded
+        + Copy
+        + Element
+        + PartialOrd
+        + Sub<Output = T>
+        + ToPrimitive
+        + Zero
+        + 'static,
+    D: Dimension,
+{
+    regional_minima(&h_minima(image, h).view())
+}
+
+/// Labeled regional maxima of the h-maxima transform.
+pub fn extended_maxima<T, D>(image: &ArrayView<T, D>, h: T) -> Array<usize, D>
+where
+    T: Add<Output = T>
+        + Bounded
+        + Copy
+        + Element
+        + PartialOrd
+        + Sub<Output = T>
+        + ToPrimitive
+        + Zero
+        + 'static,
+    D: Dimension,
+{
+    regional_maxima(&h_maxima(image, h).view())
+}
+
+#[test]
+fn test_extrema() {
+    let image = array![[3u8, 1, 3, 2, 2, 3, 0, 3]];
+
+    assert_eq!(
+        regional_minima(&image.view()),
+        array![[0, 1, 0, 2, 2, 0, 3, 0]]
+    );
+    assert_eq!(
+        regional_maxima(&image.view()),
+        array![[1, 0, 2, 0, 0, 3, 0, 4]]
+    );
+
+    assert_eq!(h_minima(&image.view(), 1), array![[3, 2, 3, 3, 3, 3, 1, 3]]);
+    assert_eq!(h_minima(&image.view(), 2), array![[3, 3, 3, 3, 3, 3, 2, 3]]);
+    assert_eq!(
+        extended_minima(&image.view(), 1),
+        array![[0, 1, 0, 0, 0, 0, 2, 0]]
+    );
+    assert_eq!(
+        extended_minima(&image.view(), 2),
+        array![[0, 0, 0, 0, 0, 0, 1, 0]]
+    );
+
+    // saturates instead of overflowing
+    let image = array![[255u8, 0, 250, 0, 255]];
+    assert_eq!(h_maxima(&image.view(), 10), array![[245, 0, 240, 0, 245]]);
+    assert_eq!(extended_maxima(&image.view(), 10), array![[1, 0, 2, 0, 3]]);
+    assert_eq!(h_minima(&image.view(), 10), array![[255, 10, 250, 10, 255]]);
+}
+
+macro_rules! impl_extrema {
+    ($regional_name:ident, $h_name:ident, $ty:ty, $dim:expr) => {
+        #[pyfunction]
+        pub fn $regional_name<'py>(
+            py: Python<'py>,
+            image: PyReadonlyArray<$ty, Dim<[usize; $dim]>>,
+            maxima: bool,
+        ) -> PyResult<&'py PyArray<usize, Dim<[usize; $dim]>>> {
+            let arr = regional_extrema(&image.as_array(), maxima);
+            Ok(arr.into_pyarray(py))
+        }
+
+        #[pyfunction]
+        pub fn $h_name<'py>(
+            py: Python<'py>,
+            image: PyReadonlyArray<$ty, Dim<[usize; $dim]>>,
+            h: $ty,
+            maxima: bool,
+        ) -> PyResult<&'py PyArray<$ty, Dim<[usize; $dim]>>> {
+            let arr = if maxima {
+                h_maxima(&image.as_array(), h)
+            } else {
+                h_minima(&image.as_array(), h)
+            };
+            Ok(arr.into_pyarray(py))
+        }
+    };
+}
+
+impl_extrema!(regional_extrema_u8_2d, h_extrema_u8_2d, u8, 2);
+impl_extrema!(regional_extrema_u8_3d, h_extrema_u8_3d, u8, 3);
+impl_extrema!(regional_extrema_u16_2d, h_extrema_u16_2d, u16, 2);
+impl_extrema!(regional_extrema_u16_3d, h_extrema_u16_3d, u16, 3);
+impl_extrema!(regional_extrema_u32_2d, h_extrema_u32_2d, u32, 2);
+impl_extrema!(regional_extrema_u32_3d, h_extrema_u32_3d, u32, 3);
+
+impl_extrema!(regional_extrema_i16_2d, h_extrema_i16_2d, i16, 2);
+impl_extrema!(regional_extrema_i16_3d, h_extrema_i16_3d, i16, 3);
+impl_extrema!(regional_extrema_i32_2d, h_extrema_i32_2d, i32, 2);
+impl_extrema!(regional_extrema_i32_3d, h_extrema_i32_3d, i32, 3);
+impl_extrema!(regional_extrema_i64_2d, h_extrema_i64_2d, i64, 2);
+impl_extrema!(regional_extrema_i64_3d, h_extrema_i64_3d, i64, 3);
+
+impl_extrema!(regional_extrema_f32_2d, h_extrema_f32_2d, f32, 2);
+impl_extrema!(regional_extrema_f32_3d, h_extrema_f32_3d, f32, 3);
+impl_extrema!(regional_extrema_f64_2d, h_extrema_f64_2d, f64, 2);
+impl_extrema!(regional_extrema_f64_3d, h_extrema_f64_3d, f64, 3);
@@ -0,0 +1,90 @@
+use ndarray::*;
+use numpy::{Element, IntoPyArray, PyArray};
+use pyo3::prelude::*;
+
+pub type PyForest<'py, T, D> = (
+    &'py PyArray<usize, D>,
+    &'py PyArray<T, D>,
+    &'py PyArray<i64, D>,
+    &'py PyArray<i64, D>,
+);
+
+/// Result of an image foresting transform.
+///
+/// Predecessors and roots are flat indices into the image, pixels without predecessor
+/// (roots and pixels outside the mask) are marked with -1.
+pub struct OptimumPathForest<T, D>
+where
+    D: Dimension,
+{
+    pub labels: Array<usize, D>,
+    pub costs: Array<T, D>,
+    pub preds: Array<i64, D>,
+    pub roots: Array<i64, D>,
+}
+
+impl<T, D> OptimumPathForest<T, D>
+where
+    T: Element,
+    D: Dimension,
+{
+    pub fn from_flat(
+        shape: D,
+        labels: Array1<usize>,
+        costs: Array1<T>,
+        preds: Array1<i64>,
+        roots: Array1<i64>,
+    ) -> Self {
+        let forest: OptimumPathForest<T, Ix1> = OptimumPathForest {
+            labels,
+            costs,
+            preds,
+            roots,
+        };
+        forest.into_shape(shape)
+    }
+
+    pub fn into_shape<E: Dimension>(self, shape: E) -> OptimumPathForest<T, E> {
+        OptimumPathForest {
+            labels: self.labels.into_shape(shape.clone()).unwrap(),
+            costs: self.costs.into_shape(shape.clone()).unwrap(),
+            preds: self.preds.into_shape(shape.clone()).unwrap(),
+            roots: self.roots.into_shape(shape).unwrap(),
+        }
+    }
+
+    /// Flat indices of the optimum path from the root of `index` up to `index`.
+    pub fn path(&self, index: usize) -> Vec<usize> {
+        let preds = self.preds.as_slice().unwrap();
+        let mut path = vec![index];
+        let mut p = preds[index];
+        while p >= 0 {
+            path.push(p as usize);
+            p = preds[p as usize];
+        }
+        path.reverse();
+        path
+    }
+
+    pub fn into_pyarrays(self, py: Python<'_>) -> PyForest<'_, T, D> {
+        (
+            self.labels.into_pyarray(py),
+            self.costs.into_pyarray(py),
+            self.preds.into_pyarray(py),
+            self.roots.into_pyarray(py),
+        )
+    }
+}
+
+#[test]
+fn test_forest_path() {
+    let forest = OptimumPathForest::from_flat(
+        Ix2(2, 2),
+        array![1, 1, 1, 1],
+        array![0, 1, 1, 2],
+        array![-1, 0, 0, 1],
+        array![0, 0, 0, 0],
+    );
+    assert_eq!(forest.path(3), vec![0, 1, 3]);
+    assert_eq!(forest.path(0), vec![0]);
+}
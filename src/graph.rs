@@ -0,0 +1,114 @@
+use ndarray::*;
+use numpy::PyReadonlyArray1;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::adjacency::CsrGraph;
+use crate::forest::{OptimumPathForest, PyForest};
+use crate::ift::{image_foresting_transform, FMax, FSum, Seed};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GraphPathCost {
+    /// Maximum arc weight along the path.
+    Max,
+    /// Sum of the arc weights along the path, weights must be non-negative.
+    Sum,
+}
+
+/// IFT on a weighted graph from the `seeds` nodes with their `labels`, seeds have cost zero.
+pub fn graph_ift(
+    graph: &CsrGraph,
+    seeds: &[usize],
+    labels: &[usize],
+    path_cost: GraphPathCost,
+) -> OptimumPathForest<f64, Ix1> {
+    if seeds.len() != labels.len() {
+        panic!("Seeds and labels must have the same length");
+    }
+    if seeds.iter().any(|&s| s >= graph.n_nodes()) {
+        panic!("Seeds must be smaller than the number of nodes");
+    }
+
+    let seeds: Vec<Seed<f64>> = seeds
+        .iter()
+        .zip(labels)
+        .map(|(&s, &l)| Seed::new(s, 0.0, l))
+        .collect();
+    let mask = Array1::from_elem(graph.n_nodes(), true);
+    let weight = |p: usize, q: usize| graph.weight(p, q).unwrap();
+
+    match path_cost {
+        GraphPathCost::Max => {
+            image_foresting_transform(graph, &mut FMax::new(weight), &seeds, &mask.view())
+        }
+        GraphPathCost::Sum => {
+            if graph.weights().iter().any(|&w| w < 0.0) {
+                panic!("Arc weights must be non-negative for the sum path cost");
+            }
+            image_foresting_transform(graph, &mut FSum::new(weight), &seeds, &mask.view())
+        }
+    }
+}
+
+#[test]
+fn test_graph_foresting_transform() {
+    // 0 - 1 - 2 - 3 with weights 1, 5, 1 and a shortcut 0 - 3 with weight 4
+    let indptr = vec![0, 2, 4, 6, 8];
+    let indices = vec![1, 3, 0, 2, 1, 3, 2, 0];
+    let data = vec![1.0, 4.0, 1.0, 5.0, 5.0, 1.0, 1.0, 4.0];
+    let graph = CsrGraph::new(indptr, indices, data);
+    assert_eq!(graph.weight(2, 3), Some(1.0));
+    assert_eq!(graph.weight(0, 2), None);
+
+    // unsorted rows with a repeated arc
+    use crate::adjacency::Adjacency;
+    let repeated = CsrGraph::new(vec![0, 3, 3], vec![1, 0, 1], vec![7.0, 2.0, 3.0]);
+    assert_eq!(repeated.neighbors(0), vec![0, 1]);
+    assert_eq!(repeated.weight(0, 1), Some(3.0));
+
+    let forest = graph_ift(&graph, &[0], &[1], GraphPathCost::Sum);
+    assert_eq!(forest.costs, array![0.0, 1.0, 5.0, 4.0]);
+    assert_eq!(forest.preds, array![-1, 0, 3, 0]);
+    assert_eq!(forest.labels, array![1, 1, 1, 1]);
+
+    let forest = graph_ift(&graph, &[0], &[1], GraphPathCost::Max);
+    assert_eq!(forest.costs, array![0.0, 1.0, 4.0, 4.0]);
+    assert_eq!(forest.preds, array![-1, 0, 3, 0]);
+
+    let forest = graph_ift(&graph, &[1, 2], &[1, 2], GraphPathCost::Max);
+    assert_eq!(forest.labels, array![1, 1, 2, 2]);
+    assert_eq!(forest.roots, array![1, 1, 2, 2]);
+}
+
+#[pyfunction]
+pub fn graph_foresting_transform<'py>(
+    py: Python<'py>,
+    indptr: PyReadonlyArray1<usize>,
+    indices: PyReadonlyArray1<usize>,
+    data: PyReadonlyArray1<f64>,
+    seeds: PyReadonlyArray1<usize>,
+    labels: PyReadonlyArray1<usize>,
+    path_cost: &str,
+) -> PyResult<PyForest<'py, f64, Ix1>> {
+    let path_cost = match path_cost {
+        "max" => GraphPathCost::Max,
+        "sum" => GraphPathCost::Sum,
+        _ => {
+            return Err(PyValueError::new_err(format!(
+                "Unknown path cost {path_cost}, must be 'max' or 'sum'."
+            )))
+        }
+    };
+    let graph = CsrGraph::new(
+        indptr.as_array().to_vec(),
+        indices.as_array().to_vec(),
+        data.as_array().to_vec(),
+    );
+    let forest = graph_ift(
+        &graph,
+        &seeds.as_array().to_vec(),
+        &labels.as_array().to_vec(),
+        path_cost,
+    );
+    Ok(forest.into_pyarrays(py))
+}
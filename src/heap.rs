@@ -1,4 +1,4 @@
-use crate::priority_queue::{ElemStatus, PriorityQueue};
+use crate::priority_queue::{ElemStatus, Policy, PriorityQueue};
 use ndarray::prelude::*;
 use num_traits::Bounded;
 use numpy::Element;
@@ -14,6 +14,7 @@ where
     size: usize,
     pub status: Vec<ElemStatus>,
     ages: Vec<i64>,
+    policy: Policy,
 }
 
 impl<'a, T: 'static> PriorityQueue<'a, T> for Heap<'a, T>
@@ -78,7 +79,10 @@ where
         }
 
         let value = self.values[index];
-        self.values[index] = T::min_value();
+        self.values[index] = match self.policy {
+            Policy::Min => T::min_value(),
+            Policy::Max => T::max_value(),
+        };
         self.move_up_from_position(self.pos[index]);
 
         self.pop()?;
@@ -95,9 +99,9 @@ where
         self.values[index] = value;
 
         if self.status[index] == ElemStatus::IN {
-            if value < prev_value {
+            if self.precedes_value(value, prev_value) {
                 self.move_up(index, parent_index)
-            } else if value > prev_value {
+            } else if self.precedes_value(prev_value, value) {
                 self.move_down(index, parent_index)
             }
         }
@@ -129,6 +133,10 @@ where
     T: Bounded + Copy + Clone + Element + PartialOrd,
 {
     pub fn new(values: &'a mut Array1<T>) -> Self {
+        Heap::with_policy(values, Policy::Min)
+    }
+
+    pub fn with_policy(values: &'a mut Array1<T>, policy: Policy) -> Self {
         let size = values.len();
         if size < 1 {
             panic!("Heap size must be greater than 0");
@@ -145,6 +153,7 @@ where
             size,
             status,
             ages,
+            policy,
         };
         heap.reset();
         heap
@@ -186,25 +195,27 @@ where
         self.pos[self.nodes[j]] = j;
     }
 
-    fn lower(&self, i: usize, j: usize) -> bool {
-        let node_i = self.nodes[i];
-        let node_j = self.nodes[j];
-        (self.values[node_i] < self.values[node_j])
-            || (self.values[node_i] == self.values[node_j] && self.ages[node_i] < self.ages[node_j])
+    #[inline(always)]
+    fn precedes_value(&self, a: T, b: T) -> bool {
+        match self.policy {
+            Policy::Min => a < b,
+            Policy::Max => a > b,
+        }
     }
 
-    fn greater(&self, i: usize, j: usize) -> bool {
+    // node at position i must be popped before node at position j
+    fn precedes(&self, i: usize, j: usize) -> bool {
         let node_i = self.nodes[i];
         let node_j = self.nodes[j];
-        (self.values[node_i] > self.values[node_j])
-            || (self.values[node_i] == self.values[node_j] && self.ages[node_i] > self.ages[node_j])
+        self.precedes_value(self.values[node_i], self.values[node_j])
+            || (self.values[node_i] == self.values[node_j] && self.ages[node_i] < self.ages[node_j])
     }
 
-    // moves towards root (smaller values) of heap
+    // moves towards root (higher priority) of heap
     fn move_up_from_position(&mut self, pos: usize) {
         let mut current = pos;
         let mut parent = self.parent(current);
-        while (parent >= 0) && self.greater(parent as usize, current) {
+        while (parent >= 0) && self.precedes(current, parent as usize) {
             self.swap(parent as usize, current);
             current = parent as usize;
             parent = self.parent(current);
@@ -215,10 +226,10 @@ where
         let mut next = pos;
         let left = self.left_child(pos);
         let right = self.right_child(pos);
-        if (left <= self.last) && self.lower(left, next) {
+        if (left <= self.last) && self.precedes(left, next) {
             next = left;
         }
-        if (right <= self.last) && self.lower(right, next) {
+        if (right <= self.last) && self.precedes(right, next) {
             next = right;
         }
         if next != pos {
@@ -301,3 +312,22 @@ fn test_heap() {
         assert_eq!(heap.status[i], ElemStatus::OUT);
     }
 }
+
+#[test]
+fn test_heap_max_policy() {
+    let mut values = Array1::from(vec![3, 1, 2, 4]);
+    let mut heap = Heap::with_policy(&mut values, Policy::Max);
+
+    for i in 0..4 {
+        heap.insert(i, -1).unwrap();
+    }
+
+    assert_eq!(heap.pop().unwrap(), 3);
+
+    heap.update_value(1, 5, -1);
+    assert_eq!(heap.pop().unwrap(), 1);
+
+    heap.remove(2).unwrap();
+    assert_eq!(heap.pop().unwrap(), 0);
+    assert!(heap.is_empty());
+}
@@ -0,0 +1,374 @@
+use ndarray::*;
+use num_traits::{Bounded, ToPrimitive, Zero};
+use numpy::{Element, IntoPyArray, PyArray, PyArray1, PyReadonlyArray, PyReadonlyArray1};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::ops::{Add, Sub};
+
+use crate::adjacency::grid_adjacency;
+use crate::component_tree::find_root;
+use crate::extrema::regional_minima;
+use crate::watershed::watershed_from_markers;
+
+pub enum HierarchyAttribute {
+    Dynamics,
+    Area,
+    Volume,
+}
+
+/// Hierarchy of watershed segmentations, the merge tree leaves `0..n` are the catchment basins
+/// labeled `1..=n` and the internal nodes are sorted by their altitude, the root is its own parent.
+pub struct WatershedHierarchy<D> {
+    pub basins: Array<usize, D>,
+    pub parents: Vec<usize>,
+    pub altitudes: Vec<f64>,
+    /// altitude at which the basins on each side of a boundary pixel are merged, zero inside basins
+    pub saliency: Array<f64, D>,
+}
+
+/// Extends the binary partition tree by merging the trees of the basins `a` and `b` at `altitude`,
+/// returns the index of the new node or `None` when they are already merged.
+fn merge(
+    zpar: &mut [usize],
+    tree_node: &mut [usize],
+    parents: &mut Vec<usize>,
+    altitudes: &mut Vec<f64>,
+    a: usize,
+    b: usize,
+    altitude: f64,
+) -> Option<usize> {
+    let ra = find_root(zpar, a);
+    let rb = find_root(zpar, b);
+    if ra == rb {
+        return None;
+    }
+    let node = parents.len();
+    parents[tree_node[ra]] = node;
+    parents[tree_node[rb]] = node;
+    parents.push(node);
+    altitudes.push(altitude);
+    zpar[rb] = ra;
+    tree_node[ra] = node;
+    Some(node)
+}
+
+/// Hierarchical watershed of `topology` where the basins of its regional minima are merged in the
+/// order of the extinction values of the given `attribute`.
+pub fn hierarchical_watershed<T, D>(
+    topology: &ArrayView<T, D>,
+    attribute: HierarchyAttribute,
+) -> WatershedHierarchy<D>
+where
+    T: Add<Output = T>
+        + Sub<Output = T>
+        + Bounded
+        + Copy
+        + Clone
+        + Debug
+        + Element
+        + PartialOrd
+        + ToPrimitive
+        + Zero
+        + 'static,
+    D: Dimension,
+{
+    let shape = topology.raw_dim();
+    let adj = grid_adjacency(topology.shape());
+
+    let minima = regional_minima(topology);
+    let mask = Array::from_elem(shape.clone(), true);
+    let basins =
+        watershed_from_markers(topology, &minima.view(), &mask.view(), false, false).labels;
+
+    let n_basins = minima.iter().copied().max().unwrap_or(0);
+    let size = topology.len();
+    let topology = topology.to_shape(size).unwrap();
+    let flat_basins = basins.to_shape(size).unwrap();
+    // the watershed reaches every pixel, each one belongs to a basin
+    let basin = |p: usize| {
+        flat_basins[p]
+            .checked_sub(1)
+            .expect("Every pixel must belong to a basin")
+    };
+
+    // region adjacency graph weighted by the pass values between basins
+    let mut edges: Vec<(usize, usize)> = Vec::new();
+    let mut weights: Vec<f64> = Vec::new();
+    let mut edge_index = HashMap::new();
+    let mut area = vec![0; n_basins];
+    let mut min_level = vec![f64::INFINITY; n_basins];
+
+    for p in 0..size {
+        let a = basin(p);
+        let level_p = topology[p].to_f64().unwrap();
+        area[a] += 1;
+        min_level[a] = min_level[a].min(level_p);
+        for q in adj.neighbors(p) {
+            let b = basin(q);
+            if q < p || a == b {
+                continue;
+            }
+            let pass = level_p.max(topology[q].to_f64().unwrap());
+            let key = (a.min(b), a.max(b));
+            let e = *edge_index.entry(key).or_insert_with(|| {
+                edges.push(key);
+                weights.push(f64::INFINITY);
+                edges.len() - 1
+            });
+            weights[e] = weights[e].min(pass);
+        }
+    }
+
+    let mut order: Vec<usize> = (0..edges.len()).collect();
+    order.sort_by(|&i, &j| weights[i].total_cmp(&weights[j]));
+
+    // binary partition tree by altitude, its merges are the minimum spanning tree edges
+    let mut zpar: Vec<usize> = (0..n_basins).collect();
+    let mut tree_node: Vec<usize> = (0..n_basins).collect();
+    let mut bpt_parents: Vec<usize> = (0..n_basins).collect();
+    let mut bpt_altitudes: Vec<f64> = min_level.clone();
+    let mut mst = Vec::new();
+    for &e in &order {
+        let (a, b) = edges[e];
+        if let Some(node) = merge(
+            &mut zpar,
+            &mut tree_node,
+            &mut bpt_parents,
+            &mut bpt_altitudes,
+            a,
+            b,
+            weights[e],
+        ) {
+            debug_assert_eq!(node, n_basins + mst.len());
+            mst.push(e);
+        }
+    }
+
+    // extinction value of each merge, the smallest attribute of its children at the merge altitude
+    let n_nodes = bpt_parents.len();
+    let mut children = vec![Vec::with_capacity(2); n_nodes];
+    for k in 0..n_nodes {
+        if bpt_parents[k] != k {
+            children[bpt_parents[k]].push(k);
+        }
+    }
+
+    area.resize(n_nodes, 0);
+    min_level.resize(n_nodes, f64::INFINITY);
+    // volume below the node altitude
+    let mut inner_volume = vec![0.0; n_nodes];
+    let mut persistence = vec![0.0; mst.len()];
+    for k in n_basins..n_nodes {
+        let altitude = bpt_altitudes[k];
+        let mut extinction = f64::INFINITY;
+        for &c in &children[k] {
+            let volume = inner_volume[c] + area[c] as f64 * (altitude - bpt_altitudes[c]);
+            let value = match attribute {
+                HierarchyAttribute::Dynamics => altitude - min_level[c],
+                HierarchyAttribute::Area => area[c] as f64,
+                HierarchyAttribute::Volume => volume,
+            };
+            extinction = extinction.min(value);
+            area[k] += area[c];
+            min_level[k] = min_level[k].min(min_level[c]);
+            inner_volume[k] += volume;
+        }
+        persistence[k - n_basins] = extinction;
+    }
+
+    // hierarchical watershed, the minimum spanning tree reweighted by the extinction values
+    let mut mst_order: Vec<usize> = (0..mst.len()).collect();
+    mst_order.sort_by(|&i, &j| persistence[i].total_cmp(&persistence[j]));
+
+    let mut zpar: Vec<usize> = (0..n_basins).collect();
+    let mut tree_node: Vec<usize> = (0..n_basins).collect();
+    let mut parents: Vec<usize> = (0..n_basins).collect();
+    let mut altitudes = vec![0.0; n_basins];
+    let mut members: Vec<Vec<usize>> = (0..n_basins).map(|b| vec![b]).collect();
+    let mut neighbors = vec![Vec::new(); n_basins];
+    for (e, &(a, b)) in edges.iter().enumerate() {
+        neighbors[a].push((b, e));
+        neighbors[b].push((a, e));
+    }
+    let mut edge_saliency = vec![0.0; edges.len()];
+
+    for &i in &mst_order {
+        let (a, b) = edges[mst[i]];
+        let (ra, rb) = (find_root(&mut zpar, a), find_root(&mut zpar, b));
+        // the boundaries between both regions get the merge altitude, visiting the smaller one
+        let (small, large) = if members[ra].len() < members[rb].len() {
+            (ra, rb)
+        } else {
+            (rb, ra)
+        };
+        for &m in &members[small] {
+            for &(o, e) in &neighbors[m] {
+                if find_root(&mut zpar, o) == large {
+                    edge_saliency[e] = persistence[i];
+                }
+            }
+        }
+        // `merge` keeps the root of `a`
+        let mut merged = std::mem::take(&mut members[large]);
+        merged.extend(std::mem::take(&mut members[small]));
+        members[ra] = merged;
+
+        merge(
+            &mut zpar,
+            &mut tree_node,
+            &mut parents,
+            &mut altitudes,
+            a,
+            b,
+            persistence[i],
+        );
+    }
+
+    let mut saliency = Array1::zeros(size);
+    for p in 0..size {
+        let a = basin(p);
+        for q in adj.neighbors(p) {
+            let b = basin(q);
+            if a != b {
+                let e = edge_index[&(a.min(b), a.max(b))];
+                saliency[p] = f64::max(saliency[p], edge_saliency[e]);
+            }
+        }
+    }
+
+    WatershedHierarchy {
+        basins,
+        parents,
+        altitudes,
+        saliency: saliency.into_shape(shape).unwrap(),
+    }
+}
+
+/// Segmentation obtained by applying the first `n_merges` merges of the hierarchy,
+/// regions are labeled from 1 in raster order.
+pub fn cut_hierarchy<D>(
+    basins: &ArrayView<usize, D>,
+    parents: &[usize],
+    n_merges: usize,
+) -> Array<usize, D>
+where
+    D: Dimension,
+{
+    let n_basins = parents.len().div_ceil(2);
+    let n_applied = n_basins + n_merges;
+
+    // parents always have larger indices than their children
+    let mut region: Vec<usize> = (0..parents.len()).collect();
+    for k in (0..parents.len()).rev() {
+        let par = parents[k];
+        if par != k && par < n_applied {
+            region[k] = region[par];
+        }
+    }
+
+    let mut relabel = HashMap::new();
+    basins.mapv(|b| {
+        let next = relabel.len() + 1;
+        *relabel.entry(region[b - 1]).or_insert(next)
+    })
+}
+
+#[test]
+fn test_hierarchical_watershed() {
+    let image = array![[0, 5, 1, 3, 2, 9, 0]];
+
+    let hierarchy = hierarchical_watershed(&image.view(), HierarchyAttribute::Dynamics);
+    assert_eq!(hierarchy.basins, array![[1, 1, 2, 2, 3, 4, 4]]);
+    assert_eq!(hierarchy.parents, vec![5, 4, 4, 6, 5, 6, 6]);
+    assert_eq!(hierarchy.altitudes, vec![0.0, 0.0, 0.0, 0.0, 1.0, 4.0, 9.0]);
+    assert_eq!(
+        hierarchy.saliency,
+        array![[0.0, 4.0, 4.0, 1.0, 9.0, 9.0, 0.0]]
+    );
+
+    let hierarchy = hierarchical_watershed(&image.view(), HierarchyAttribute::Area);
+    assert_eq!(hierarchy.altitudes[4..], [1.0, 2.0, 2.0]);
+
+    let hierarchy = hierarchical_watershed(&image.view(), HierarchyAttribute::Volume);
+    assert_eq!(hierarchy.altitudes[4..], [1.0, 10.0, 18.0]);
+
+    let cut = cut_hierarchy(&hierarchy.basins.view(), &hierarchy.parents, 2);
+    assert_eq!(cut, array![[1, 1, 1, 1, 1, 2, 2]]);
+    let cut = cut_hierarchy(&hierarchy.basins.view(), &hierarchy.parents, 0);
+    assert_eq!(cut, hierarchy.basins);
+
+    // a ridge at the largest value of the dtype
+    let image = array![[0u8, 255, 1, 3, 2, 9, 0]];
+    let hierarchy = hierarchical_watershed(&image.view(), HierarchyAttribute::Dynamics);
+    assert_eq!(hierarchy.basins, array![[1, 1, 2, 2, 3, 4, 4]]);
+    assert_eq!(hierarchy.altitudes[4..], [1.0, 8.0, 255.0]);
+}
+
+macro_rules! impl_hierarchical_watershed {
+    ($new_name:ident, $ty:ty, $dim:expr) => {
+        #[pyfunction]
+        pub fn $new_name<'py>(
+            py: Python<'py>,
+            topology: PyReadonlyArray<$ty, Dim<[usize; $dim]>>,
+            attribute: &str,
+        ) -> PyResult<(
+            &'py PyArray<usize, Dim<[usize; $dim]>>,
+            &'py PyArray1<usize>,
+            &'py PyArray1<f64>,
+            &'py PyArray<f64, Dim<[usize; $dim]>>,
+        )> {
+            let attribute = match attribute {
+                "dynamics" => HierarchyAttribute::Dynamics,
+                "area" => HierarchyAttribute::Area,
+                "volume" => HierarchyAttribute::Volume,
+                _ => return Err(PyValueError::new_err("Unknown attribute.")),
+            };
+            let hierarchy = hierarchical_watershed(&topology.as_array(), attribute);
+            Ok((
+                hierarchy.basins.into_pyarray(py),
+                PyArray1::from_vec(py, hierarchy.parents),
+                PyArray1::from_vec(py, hierarchy.altitudes),
+                hierarchy.saliency.into_pyarray(py),
+            ))
+        }
+    };
+}
+
+impl_hierarchical_watershed!(hierarchical_watershed_u8_2d, u8, 2);
+impl_hierarchical_watershed!(hierarchical_watershed_u8_3d, u8, 3);
+impl_hierarchical_watershed!(hierarchical_watershed_u16_2d, u16, 2);
+impl_hierarchical_watershed!(hierarchical_watershed_u16_3d, u16, 3);
+impl_hierarchical_watershed!(hierarchical_watershed_u32_2d, u32, 2);
+impl_hierarchical_watershed!(hierarchical_watershed_u32_3d, u32, 3);
+
+impl_hierarchical_watershed!(hierarchical_watershed_i16_2d, i16, 2);
+impl_hierarchical_watershed!(hierarchical_watershed_i16_3d, i16, 3);
+impl_hierarchical_watershed!(hierarchical_watershed_i32_2d, i32, 2);
+impl_hierarchical_watershed!(hierarchical_watershed_i32_3d, i32, 3);
+impl_hierarchical_watershed!(hierarchical_watershed_i64_2d, i64, 2);
+impl_hierarchical_watershed!(hierarchical_watershed_i64_3d, i64, 3);
+
+impl_hierarchical_watershed!(hierarchical_watershed_f32_2d, f32, 2);
+impl_hierarchical_watershed!(hierarchical_watershed_f32_3d, f32, 3);
+impl_hierarchical_watershed!(hierarchical_watershed_f64_2d, f64, 2);
+impl_hierarchical_watershed!(hierarchical_watershed_f64_3d, f64, 3);
+
+macro_rules! impl_cut_hierarchy {
+    ($new_name:ident, $dim:expr) => {
+        #[pyfunction]
+        pub fn $new_name<'py>(
+            py: Python<'py>,
+            basins: PyReadonlyArray<usize, Dim<[usize; $dim]>>,
+            parents: PyReadonlyArray1<usize>,
+            n_merges: usize,
+        ) -> PyResult<&'py PyArray<usize, Dim<[usize; $dim]>>> {
+            let labels = cut_hierarchy(&basins.as_array(), parents.as_slice()?, n_merges);
+            Ok(labels.into_pyarray(py))
+        }
+    };
+}
+
+impl_cut_hierarchy!(cut_hierarchy_2d, 2);
+impl_cut_hierarchy!(cut_hierarchy_3d, 3);
@@ -0,0 +1,445 @@
+use ndarray::prelude::*;
+use num_traits::{Bounded, Float, ToPrimitive, Zero};
+use numpy::Element;
+use std::any::TypeId;
+use std::ops::{Add, Sub};
+
+use crate::adjacency::{squared_distance, Adjacency};
+use crate::bucket_queue::BucketQueue;
+use crate::forest::OptimumPathForest;
+use crate::heap::Heap;
+use crate::priority_queue::{ElemStatus, Policy, PriorityQueue};
+
+pub fn is_float<T: 'static>() -> bool {
+    let type_id = TypeId::of::<T>();
+    type_id == TypeId::of::<f32>() || type_id == TypeId::of::<f64>()
+}
+
+/// Bucket queue for non-negative integer costs, heap otherwise.
+pub fn new_queue<'a, T>(
+    values: &'a mut Array1<T>,
+    policy: Policy,
+) -> Box<dyn PriorityQueue<'a, T> + 'a>
+where
+    T: Bounded + Copy + Element + PartialOrd + Sub<Output = T> + ToPrimitive + Zero + 'static,
+{
+    let has_negative = values.iter().any(|&v| v < T::zero());
+    // decreasing signed costs may go below zero even when the initial ones are not
+    let may_go_negative = policy == Policy::Max && T::min_value() < T::zero();
+    match is_float::<T>() || has_negative || may_go_negative {
+        false => Box::new(BucketQueue::with_policy(values, policy)),
+        true => Box::new(Heap::with_policy(values, policy)),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Seed<T> {
+    pub index: usize,
+    pub cost: T,
+    pub label: usize,
+}
+
+impl<T> Seed<T> {
+    pub fn new(index: usize, cost: T, label: usize) -> Self {
+        Seed { index, cost, label }
+    }
+}
+
+pub trait PathCost<T> {
+    /// Cost of extending the optimum path of `p`, with cost `cost_p` and rooted at `root`, by the arc (p, q).
+    fn extend(&self, p: usize, q: usize, cost_p: T, root: usize) -> T;
+
+    /// Whether path costs are minimized or maximized.
+    fn policy(&self) -> Policy {
+        Policy::Min
+    }
+
+    /// Cost of `p` when it is popped as a root of the forest, defaults to its seed cost.
+    fn root_cost(&self, _p: usize, cost: T) -> T {
+        cost
+    }
+
+    /// Called once the optimum path of `p` is final.
+    fn on_pop(&mut self, _p: usize, _root: usize) {}
+
+    /// Called when `p` offers a better path to `q`.
+    fn on_conquer(&mut self, _p: usize, _q: usize) {}
+}
+
+/// f(π · <p, q>) = max(f(π), w(p, q))
+pub struct FMax<W> {
+    weight: W,
+}
+
+impl<W> FMax<W> {
+    pub fn new(weight: W) -> Self {
+        FMax { weight }
+    }
+}
+
+impl<T, W> PathCost<T> for FMax<W>
+where
+    T: Copy + PartialOrd,
+    W: Fn(usize, usize) -> T,
+{
+    #[inline(always)]
+    fn extend(&self, p: usize, q: usize, cost_p: T, _root: usize) -> T {
+        let w = (self.weight)(p, q);
+        if w > cost_p {
+            w
+        } else {
+            cost_p
+        }
+    }
+}
+
+/// f(π · <p, q>) = min(f(π), w(p, q)), maximized.
+pub struct FMin<W> {
+    weight: W,
+}
+
+impl<W> FMin<W> {
+    pub fn new(weight: W) -> Self {
+        FMin { weight }
+    }
+}
+
+impl<T, W> PathCost<T> for FMin<W>
+where
+    T: Copy + PartialOrd,
+    W: Fn(usize, usize) -> T,
+{
+    #[inline(always)]
+    fn extend(&self, p: usize, q: usize, cost_p: T, _root: usize) -> T {
+        let w = (self.weight)(p, q);
+        if w < cost_p {
+            w
+        } else {
+            cost_p
+        }
+    }
+
+    fn policy(&self) -> Policy {
+        Policy::Max
+    }
+}
+
+/// f(π · <p, q>) = f(π) + w(p, q)
+pub struct FSum<W> {
+    weight: W,
+}
+
+impl<W> FSum<W> {
+    pub fn new(weight: W) -> Self {
+        FSum { weight }
+    }
+}
+
+impl<T, W> PathCost<T> for FSum<W>
+where
+    T: Add<Output = T>,
+    W: Fn(usize, usize) -> T,
+{
+    #[inline(always)]
+    fn extend(&self, p: usize, q: usize, cost_p: T, _root: usize) -> T {
+        cost_p + (self.weight)(p, q)
+    }
+}
+
+/// f(π · <p, q>) = w(p, q), the riverbed path cost.
+pub struct FLast<W> {
+    weight: W,
+}
+
+impl<W> FLast<W> {
+    pub fn new(weight: W) -> Self {
+        FLast { weight }
+    }
+}
+
+impl<T, W> PathCost<T> for FLast<W>
+where
+    W: Fn(usize, usize) -> T,
+{
+    #[inline(always)]
+    fn extend(&self, p: usize, q: usize, _cost_p: T, _root: usize) -> T {
+        (self.weight)(p, q)
+    }
+}
+
+/// Mean arc weight along the path.
+pub struct FMean<W> {
+    weight: W,
+    length: Vec<usize>,
+}
+
+impl<W> FMean<W> {
+    pub fn new(weight: W, size: usize) -> Self {
+        FMean {
+            weight,
+            length: vec![0; size],
+        }
+    }
+}
+
+impl<T, W> PathCost<T> for FMean<W>
+where
+    T: Float,
+    W: Fn(usize, usize) -> T,
+{
+    #[inline(always)]
+    fn extend(&self, p: usize, q: usize, cost_p: T, _root: usize) -> T {
+        let length = T::from(self.length[p]).unwrap();
+        (cost_p * length + (self.weight)(p, q)) / (length + T::one())
+    }
+
+    fn on_conquer(&mut self, p: usize, q: usize) {
+        self.length[q] = self.length[p] + 1;
+    }
+}
+
+/// Squared euclidean distance between `q` and the root of the path.
+pub struct FEuclidean {
+    shape: Vec<usize>,
+    spacing: Vec<f64>,
+}
+
+impl FEuclidean {
+    pub fn new(shape: &[usize], spacing: &[f64]) -> Self {
+        if shape.len() != spacing.len() {
+            panic!("Spacing must have one value per dimension");
+        }
+        FEuclidean {
+            shape: shape.to_vec(),
+            spacing: spacing.to_vec(),
+        }
+    }
+}
+
+impl<T> PathCost<T> for FEuclidean
+where
+    T: Float,
+{
+    #[inline(always)]
+    fn extend(&self, _p: usize, q: usize, _cost_p: T, root: usize) -> T {
+        T::from(squared_distance(&self.shape, &self.spacing, root, q)).unwrap()
+    }
+}
+
+/// Computes the optimum-path forest rooted at `seeds`, paths are restricted to `mask`.
+/// Pixels that are not reached have label 0, no root nor predecessor (-1) and infinite cost.
+pub fn image_foresting_transform<T, C, A>(
+    adjacency: &A,
+    path_cost: &mut C,
+    seeds: &[Seed<T>],
+    mask: &ArrayView1<bool>,
+) -> OptimumPathForest<T, Ix1>
+where
+    T: Bounded + Copy + Element + PartialOrd + Sub<Output = T> + ToPrimitive + Zero + 'static,
+    C: PathCost<T> + ?Sized,
+    A: Adjacency + ?Sized,
+{
+    run_ift(adjacency, path_cost, seeds, mask, None, false)
+}
+
+/// Same as `image_foresting_transform`, but conquered pixels adjacent to an already popped pixel of
+/// another tree are marked as watershed lines, they get label 0 and do not propagate.
+/// Line pixels have no root nor predecessor (-1), their cost is the one of the path that reached them.
+pub fn image_foresting_transform_with_line<T, C, A>(
+    adjacency: &A,
+    path_cost: &mut C,
+    seeds: &[Seed<T>],
+    mask: &ArrayView1<bool>,
+) -> OptimumPathForest<T, Ix1>
+where
+    T: Bounded + Copy + Element + PartialOrd + Sub<Output = T> + ToPrimitive + Zero + 'static,
+    C: PathCost<T> + ?Sized,
+    A: Adjacency + ?Sized,
+{
+    run_ift(adjacency, path_cost, seeds, mask, None, true)
+}
+
+/// Same as `image_foresting_transform`, but stops as soon as the optimum path to `target` is found.
+/// Only the pixels popped until then are part of the returned forest.
+pub fn image_foresting_transform_with_target<T, C, A>(
+    adjacency: &A,
+    path_cost: &mut C,
+    seeds: &[Seed<T>],
+    mask: &ArrayView1<bool>,
+    target: Option<usize>,
+) -> OptimumPathForest<T, Ix1>
+where
+    T: Bounded + Copy + Element + PartialOrd + Sub<Output = T> + ToPrimitive + Zero + 'static,
+    C: PathCost<T> + ?Sized,
+    A: Adjacency + ?Sized,
+{
+    run_ift(adjacency, path_cost, seeds, mask, target, false)
+}
+
+fn run_ift<T, C, A>(
+    adjacency: &A,
+    path_cost: &mut C,
+    seeds: &[Seed<T>],
+    mask: &ArrayView1<bool>,
+    target: Option<usize>,
+    watershed_line: bool,
+) -> OptimumPathForest<T, Ix1>
+where
+    T: Bounded + Copy + Element + PartialOrd + Sub<Output = T> + ToPrimitive + Zero + 'static,
+    C: PathCost<T> + ?Sized,
+    A: Adjacency + ?Sized,
+{
+    let size = mask.len();
+
+    let mut cost = Array1::<T>::zeros(size);
+    let mut root = Array1::<i64>::from_elem(size, -1);
+    let mut pred = Array1::<i64>::from_elem(size, -1);
+    let mut label = Array1::<usize>::zeros(size);
+
+    for seed in seeds {
+        cost[seed.index] = seed.cost;
+        root[seed.index] = seed.index as i64;
+        label[seed.index] = seed.label;
+    }
+
+    let policy = path_cost.policy();
+    // cost of the pixels that are not reached
+    let worst = match policy {
+        Policy::Min => T::max_value(),
+        Policy::Max => T::min_value(),
+    };
+    let better = |a: T, b: T| match policy {
+        Policy::Min => a < b,
+        Policy::Max => a > b,
+    };
+
+    let mut queue = new_queue(&mut cost, policy);
+
+    for seed in seeds {
+        if mask[seed.index] && queue.get_status(seed.index) == ElemStatus::OUT {
+            queue.insert(seed.index, -1).unwrap();
+        }
+    }
+
+    while !queue.is_empty() {
+        let p = queue.pop().unwrap();
+        let mut cost_p = queue.get_value(p);
+
+        if pred[p] < 0 {
+            let root_cost = path_cost.root_cost(p, cost_p);
+            if root_cost != cost_p {
+                queue.update_value(p, root_cost, -1);
+                cost_p = root_cost;
+            }
+        }
+
+        if watershed_line && pred[p] >= 0 {
+            let meets_other_tree = adjacency.neighbors(p).into_iter().any(|q| {
+                mask[q]
+                    && queue.get_status(q) == ElemStatus::POPPED
+                    && label[q] != 0
+                    && label[q] != label[p]
+            });
+            if meets_other_tree {
+                label[p] = 0;
+                root[p] = -1;
+                pred[p] = -1;
+                continue;
+            }
+        }
+
+        path_cost.on_pop(p, root[p] as usize);
+
+        if target == Some(p) {
+            break;
+        }
+
+        for q in adjacency.neighbors(p) {
+            let status = queue.get_status(q);
+            if mask[q] && status != ElemStatus::POPPED {
+                let path_cost_q = path_cost.extend(p, q, cost_p, root[p] as usize);
+
+                // pixels out of the queue have no path yet, any offer conquers them
+                if status == ElemStatus::OUT || better(path_cost_q, queue.get_value(q)) {
+                    root[q] = root[p];
+                    pred[q] = p as i64;
+                    label[q] = label[p];
+                    path_cost.on_conquer(p, q);
+
+                    queue.update_value(q, path_cost_q, p as i64);
+                    if status == ElemStatus::OUT {
+                        queue.insert(q, p as i64).unwrap();
+                    }
+                }
+            }
+        }
+    }
+
+    let popped: Vec<bool> = (0..size)
+        .map(|i| queue.get_status(i) == ElemStatus::POPPED)
+        .collect();
+
+    drop(queue);
+
+    for i in 0..size {
+        if !popped[i] {
+            cost[i] = worst;
+            root[i] = -1;
+            pred[i] = -1;
+            label[i] = 0;
+        }
+    }
+
+    OptimumPathForest::from_flat(Ix1(size), label, cost, pred, root)
+}
+
+#[test]
+fn test_ift_path_costs() {
+    use crate::adjacency::AdjacencyGrid2D;
+
+    let adj = AdjacencyGrid2D::new(&[3, 3]);
+    let mask = Array1::from_elem(9, true);
+
+    // fsum with unit arcs is the city-block distance
+    let seeds = vec![Seed::new(4, 0, 1)];
+    let forest = image_foresting_transform(&adj, &mut FSum::new(|_, _| 1), &seeds, &mask.view());
+    assert_eq!(forest.costs, array![2, 1, 2, 1, 0, 1, 2, 1, 2]);
+    assert_eq!(forest.labels, Array1::from_elem(9, 1));
+    assert_eq!(forest.preds[4], -1);
+    assert_eq!(forest.roots, Array1::from_elem(9, 4));
+
+    // fmin is maximized, it's the superior reconstruction of the marker
+    let image = array![5, 2, 4, 3, 1, 6, 2, 2, 7];
+    let seeds = vec![Seed::new(0, 5, 1), Seed::new(8, 6, 2)];
+    let forest =
+        image_foresting_transform(&adj, &mut FMin::new(|_, q| image[q]), &seeds, &mask.view());
+    assert_eq!(forest.costs, array![5, 2, 4, 3, 1, 6, 2, 2, 6]);
+    assert_eq!(forest.labels[2], 2);
+    assert_eq!(forest.labels[3], 1);
+
+    // feuclidean and the mask
+    let mut mask = Array1::from_elem(9, true);
+    mask[2] = false;
+    let seeds = vec![Seed::new(0, 0.0, 1)];
+    let mut path_cost = FEuclidean::new(&[3, 3], &[1.0, 2.0]);
+    let forest = image_foresting_transform(&adj, &mut path_cost, &seeds, &mask.view());
+    assert_eq!(
+        forest.costs,
+        array![0.0, 4.0, f64::MAX, 1.0, 5.0, 17.0, 4.0, 8.0, 20.0]
+    );
+    assert_eq!(forest.labels[2], 0);
+    assert_eq!(forest.roots[2], -1);
+
+    // fmean
+    let seeds = vec![Seed::new(0, 0.0, 1)];
+    let mask = Array1::from_elem(3, true);
+    let adj = AdjacencyGrid2D::new(&[1, 3]);
+    let weights = [0.0, 2.0, 4.0];
+    let forest = image_foresting_transform(
+        &adj,
+        &mut FMean::new(|_, q| weights[q], 3),
+        &seeds,
+        &mask.view(),
+    );
+    assert_eq!(forest.costs, array![0.0, 2.0, 3.0]);
+}
@@ -0,0 +1,116 @@
+use ndarray::*;
+use num_traits::{Bounded, FromPrimitive, ToPrimitive};
+use numpy::{Element, IntoPyArray, PyArray, PyArray1, PyReadonlyArray};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::collections::HashSet;
+
+/// Sorted non-zero labels of `labels` preceded by zero, the inverse map of the sequential
+/// relabeling.
+pub fn sequential_inverse_map<D: Dimension>(labels: &ArrayView<usize, D>) -> Vec<usize> {
+    let max_label = labels.iter().copied().max().unwrap_or(0);
+    if max_label <= labels.len() {
+        // dense presence table, already sorted
+        let mut present = vec![false; max_label + 1];
+        labels.iter().for_each(|&l| present[l] = true);
+        present[0] = true;
+        return (0..=max_label).filter(|&l| present[l]).collect();
+    }
+    let unique: HashSet<usize> = labels.iter().copied().filter(|&l| l > 0).collect();
+    let mut inverse: Vec<usize> = unique.into_iter().collect();
+    inverse.push(0);
+    inverse.sort_unstable();
+    inverse
+}
+
+/// Labels replaced by their index in the sorted `inverse`, zero is kept as background.
+/// Uses a dense lookup table when the largest label is below the image size, a binary search
+/// otherwise.
+pub fn relabel_with_inverse_map<T, D>(
+    labels: &ArrayView<usize, D>,
+    inverse: &[usize],
+) -> Array<T, D>
+where
+    T: Bounded + Copy + FromPrimitive + ToPrimitive,
+    D: Dimension,
+{
+    if inverse.len() - 1 > T::max_value().to_usize().unwrap_or(usize::MAX) {
+        panic!("Too many labels for the output type");
+    }
+    let new_label = |i: usize| T::from_usize(i).unwrap();
+    let max_label = *inverse.last().unwrap();
+    if max_label <= labels.len() {
+        let mut forward = vec![new_label(0); max_label + 1];
+        for (new, &old) in inverse.iter().enumerate() {
+            forward[old] = new_label(new);
+        }
+        labels.mapv(|l| forward[l])
+    } else {
+        labels.mapv(|l| new_label(inverse.binary_search(&l).unwrap()))
+    }
+}
+
+/// Relabels `labels` to 1..=N keeping their order, returns the relabeled image and the inverse map
+/// from the new labels to the original ones.
+pub fn relabel_sequential<T, D>(labels: &ArrayView<usize, D>) -> (Array<T, D>, Vec<usize>)
+where
+    T: Bounded + Copy + FromPrimitive + ToPrimitive,
+    D: Dimension,
+{
+    let inverse = sequential_inverse_map(labels);
+    (relabel_with_inverse_map(labels, &inverse), inverse)
+}
+
+#[test]
+fn test_relabel_sequential() {
+    let labels = array![[0, 1001, 1001], [7, 0, 52]];
+
+    let (relabeled, inverse) = relabel_sequential::<u16, _>(&labels.view());
+    assert_eq!(relabeled, array![[0, 3, 3], [1, 0, 2]]);
+    assert_eq!(inverse, vec![0, 7, 52, 1001]);
+
+    let (relabeled, _) = relabel_sequential::<u64, _>(&array![[0, 0]].view());
+    assert_eq!(relabeled, array![[0, 0]]);
+
+    // labels below the image size use the lookup table
+    let (relabeled, inverse) = relabel_sequential::<u32, _>(&array![[4, 0, 2], [4, 6, 6]].view());
+    assert_eq!(relabeled, array![[2, 0, 1], [2, 3, 3]]);
+    assert_eq!(inverse, vec![0, 2, 4, 6]);
+}
+
+#[test]
+#[should_panic]
+fn test_relabel_sequential_overflow() {
+    let many = Array1::from_iter(0..300);
+    relabel_sequential::<u8, _>(&many.view());
+}
+
+macro_rules! impl_relabel_sequential {
+    ($new_name:ident, $ty:ty, $dim:expr) => {
+        #[pyfunction]
+        pub fn $new_name<'py>(
+            py: Python<'py>,
+            labels: PyReadonlyArray<usize, Dim<[usize; $dim]>>,
+        ) -> PyResult<(&'py PyArray<$ty, Dim<[usize; $dim]>>, &'py PyArray1<usize>)> {
+            let labels = labels.as_array();
+            let inverse = sequential_inverse_map(&labels);
+            if inverse.len() - 1 > <$ty>::MAX as usize {
+                return Err(PyValueError::new_err(format!(
+                    "{} labels do not fit in {}.",
+                    inverse.len() - 1,
+                    <$ty as Element>::get_dtype(py)
+                )));
+            }
+            let relabeled: Array<$ty, Dim<[usize; $dim]>> =
+                relabel_with_inverse_map(&labels, &inverse);
+            Ok((relabeled.into_pyarray(py), PyArray1::from_vec(py, inverse)))
+        }
+    };
+}
+
+impl_relabel_sequential!(relabel_sequential_u16_2d, u16, 2);
+impl_relabel_sequential!(relabel_sequential_u16_3d, u16, 3);
+impl_relabel_sequential!(relabel_sequential_u32_2d, u32, 2);
+impl_relabel_sequential!(relabel_sequential_u32_3d, u32, 3);
+impl_relabel_sequential!(relabel_sequential_u64_2d, u64, 2);
+impl_relabel_sequential!(relabel_sequential_u64_3d, u64, 3);
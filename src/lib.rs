@@ -1,11 +1,47 @@
 use pyo3::prelude::*;
 use pyo3::wrap_pyfunction;
 
-mod adjacency;
-mod bucket_queue;
-mod heap;
-mod priority_queue;
+pub mod adjacency;
+mod arc_weights;
+mod attribute_filters;
+pub mod bucket_queue;
+mod component_tree;
+mod dift;
+mod distance;
+mod dynamic_trees;
+mod extrema;
+mod forest;
+mod graph;
+pub mod heap;
+mod hierarchy;
+mod ift;
+mod labels;
+mod minimal_path;
+mod opf;
+mod oriented;
+pub mod priority_queue;
+mod rag;
+mod reconstruction;
+mod superpixels;
 mod watershed;
+pub use arc_weights::*;
+pub use attribute_filters::*;
+pub use component_tree::*;
+pub use dift::*;
+pub use distance::*;
+pub use dynamic_trees::*;
+pub use extrema::*;
+pub use forest::*;
+pub use graph::*;
+pub use hierarchy::*;
+pub use ift::*;
+pub use labels::*;
+pub use minimal_path::*;
+pub use opf::*;
+pub use oriented::*;
+pub use rag::*;
+pub use reconstruction::*;
+pub use superpixels::*;
 pub use watershed::*;
 
 #[pymodule]
@@ -29,5 +65,221 @@ fn _rust_ift(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(watershed_from_minima_f64_2d, m)?)?;
     m.add_function(wrap_pyfunction!(watershed_from_minima_f64_3d, m)?)?;
 
+    m.add_function(wrap_pyfunction!(watershed_from_markers_u8_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(watershed_from_markers_u8_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(watershed_from_markers_u16_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(watershed_from_markers_u16_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(watershed_from_markers_u32_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(watershed_from_markers_u32_3d, m)?)?;
+
+    m.add_function(wrap_pyfunction!(watershed_from_markers_i16_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(watershed_from_markers_i16_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(watershed_from_markers_i32_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(watershed_from_markers_i32_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(watershed_from_markers_i64_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(watershed_from_markers_i64_3d, m)?)?;
+
+    m.add_function(wrap_pyfunction!(watershed_from_markers_f32_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(watershed_from_markers_f32_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(watershed_from_markers_f64_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(watershed_from_markers_f64_3d, m)?)?;
+
+    m.add_function(wrap_pyfunction!(compact_watershed_u8_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(compact_watershed_u8_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(compact_watershed_u16_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(compact_watershed_u16_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(compact_watershed_u32_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(compact_watershed_u32_3d, m)?)?;
+
+    m.add_function(wrap_pyfunction!(compact_watershed_i16_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(compact_watershed_i16_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(compact_watershed_i32_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(compact_watershed_i32_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(compact_watershed_i64_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(compact_watershed_i64_3d, m)?)?;
+
+    m.add_function(wrap_pyfunction!(compact_watershed_f32_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(compact_watershed_f32_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(compact_watershed_f64_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(compact_watershed_f64_3d, m)?)?;
+
+    m.add_function(wrap_pyfunction!(arc_weight_watershed_from_minima_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(arc_weight_watershed_from_minima_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(arc_weight_watershed_from_markers_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(arc_weight_watershed_from_markers_3d, m)?)?;
+
+    m.add_function(wrap_pyfunction!(hierarchical_watershed_u8_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(hierarchical_watershed_u8_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(hierarchical_watershed_u16_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(hierarchical_watershed_u16_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(hierarchical_watershed_u32_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(hierarchical_watershed_u32_3d, m)?)?;
+
+    m.add_function(wrap_pyfunction!(hierarchical_watershed_i16_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(hierarchical_watershed_i16_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(hierarchical_watershed_i32_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(hierarchical_watershed_i32_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(hierarchical_watershed_i64_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(hierarchical_watershed_i64_3d, m)?)?;
+
+    m.add_function(wrap_pyfunction!(hierarchical_watershed_f32_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(hierarchical_watershed_f32_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(hierarchical_watershed_f64_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(hierarchical_watershed_f64_3d, m)?)?;
+
+    m.add_function(wrap_pyfunction!(cut_hierarchy_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(cut_hierarchy_3d, m)?)?;
+
+    m.add_function(wrap_pyfunction!(euclidean_distance_transform_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(euclidean_distance_transform_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(geodesic_distance_transform_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(geodesic_distance_transform_3d, m)?)?;
+
+    m.add_function(wrap_pyfunction!(dynamic_tree_watershed_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(dynamic_tree_watershed_3d, m)?)?;
+
+    m.add_function(wrap_pyfunction!(oriented_watershed_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(oriented_watershed_3d, m)?)?;
+
+    m.add_function(wrap_pyfunction!(graph_foresting_transform, m)?)?;
+
+    m.add_function(wrap_pyfunction!(minimal_path_2d, m)?)?;
+
+    m.add_function(wrap_pyfunction!(isf_superpixels_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(isf_superpixels_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(disf_superpixels_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(disf_superpixels_3d, m)?)?;
+
+    m.add_function(wrap_pyfunction!(opf_clustering_samples, m)?)?;
+
+    m.add_function(wrap_pyfunction!(relabel_sequential_u16_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(relabel_sequential_u16_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(relabel_sequential_u32_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(relabel_sequential_u32_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(relabel_sequential_u64_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(relabel_sequential_u64_3d, m)?)?;
+
+    m.add_function(wrap_pyfunction!(region_adjacency_graph_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(region_adjacency_graph_3d, m)?)?;
+
+    m.add_function(wrap_pyfunction!(reconstruction_u8_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(reconstruction_u8_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(reconstruction_u16_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(reconstruction_u16_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(reconstruction_u32_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(reconstruction_u32_3d, m)?)?;
+
+    m.add_function(wrap_pyfunction!(reconstruction_i16_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(reconstruction_i16_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(reconstruction_i32_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(reconstruction_i32_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(reconstruction_i64_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(reconstruction_i64_3d, m)?)?;
+
+    m.add_function(wrap_pyfunction!(reconstruction_f32_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(reconstruction_f32_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(reconstruction_f64_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(reconstruction_f64_3d, m)?)?;
+
+    m.add_function(wrap_pyfunction!(regional_extrema_u8_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(regional_extrema_u8_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(regional_extrema_u16_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(regional_extrema_u16_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(regional_extrema_u32_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(regional_extrema_u32_3d, m)?)?;
+
+    m.add_function(wrap_pyfunction!(regional_extrema_i16_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(regional_extrema_i16_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(regional_extrema_i32_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(regional_extrema_i32_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(regional_extrema_i64_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(regional_extrema_i64_3d, m)?)?;
+
+    m.add_function(wrap_pyfunction!(regional_extrema_f32_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(regional_extrema_f32_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(regional_extrema_f64_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(regional_extrema_f64_3d, m)?)?;
+
+    m.add_function(wrap_pyfunction!(h_extrema_u8_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(h_extrema_u8_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(h_extrema_u16_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(h_extrema_u16_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(h_extrema_u32_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(h_extrema_u32_3d, m)?)?;
+
+    m.add_function(wrap_pyfunction!(h_extrema_i16_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(h_extrema_i16_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(h_extrema_i32_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(h_extrema_i32_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(h_extrema_i64_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(h_extrema_i64_3d, m)?)?;
+
+    m.add_function(wrap_pyfunction!(h_extrema_f32_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(h_extrema_f32_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(h_extrema_f64_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(h_extrema_f64_3d, m)?)?;
+
+    m.add_function(wrap_pyfunction!(area_filter_u8_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(area_filter_u8_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(area_filter_u16_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(area_filter_u16_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(area_filter_u32_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(area_filter_u32_3d, m)?)?;
+
+    m.add_function(wrap_pyfunction!(area_filter_i16_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(area_filter_i16_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(area_filter_i32_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(area_filter_i32_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(area_filter_i64_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(area_filter_i64_3d, m)?)?;
+
+    m.add_function(wrap_pyfunction!(area_filter_f32_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(area_filter_f32_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(area_filter_f64_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(area_filter_f64_3d, m)?)?;
+
+    m.add_function(wrap_pyfunction!(volume_filter_u8_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(volume_filter_u8_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(volume_filter_u16_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(volume_filter_u16_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(volume_filter_u32_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(volume_filter_u32_3d, m)?)?;
+
+    m.add_function(wrap_pyfunction!(volume_filter_i16_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(volume_filter_i16_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(volume_filter_i32_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(volume_filter_i32_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(volume_filter_i64_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(volume_filter_i64_3d, m)?)?;
+
+    m.add_function(wrap_pyfunction!(volume_filter_f32_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(volume_filter_f32_3d, m)?)?;
+    m.add_function(wrap_pyfunction!(volume_filter_f64_2d, m)?)?;
+    m.add_function(wrap_pyfunction!(volume_filter_f64_3d, m)?)?;
+
+    m.add_class::<DifferentialWatershedU8>()?;
+    m.add_class::<DifferentialWatershedU16>()?;
+    m.add_class::<DifferentialWatershedU32>()?;
+
+    m.add_class::<DifferentialWatershedI16>()?;
+    m.add_class::<DifferentialWatershedI32>()?;
+    m.add_class::<DifferentialWatershedI64>()?;
+
+    m.add_class::<DifferentialWatershedF32>()?;
+    m.add_class::<DifferentialWatershedF64>()?;
+
+    m.add_class::<ComponentTreeU8>()?;
+    m.add_class::<ComponentTreeU16>()?;
+    m.add_class::<ComponentTreeU32>()?;
+
+    m.add_class::<ComponentTreeI16>()?;
+    m.add_class::<ComponentTreeI32>()?;
+    m.add_class::<ComponentTreeI64>()?;
+
+    m.add_class::<ComponentTreeF32>()?;
+    m.add_class::<ComponentTreeF64>()?;
+
+    m.add_class::<OpfClassifierModel>()?;
+
     Ok(())
 }
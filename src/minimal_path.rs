@@ -0,0 +1,104 @@
+use ndarray::*;
+use numpy::{IntoPyArray, PyArray2, PyReadonlyArray2};
+use pyo3::prelude::*;
+
+use crate::adjacency::{squared_distance, AdjacencyGrid2D};
+use crate::ift::{image_foresting_transform_with_target, FLast, FSum, Seed};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MinimalPathCost {
+    /// Live-wire, sum of the arc weights.
+    Additive,
+    /// Riverbed, weight of the last arc.
+    Riverbed,
+}
+
+/// Optimum path between `start` and `end` on a 2D `cost` image with 8-connectivity.
+/// The arc weight w(p, q) is the cost of `q` scaled by the arc length, the IFT stops once `end` is reached.
+/// Returns the (y, x) coordinates of the path from `start` to `end`, empty when `end` is not reachable.
+pub fn minimal_path(
+    cost: &ArrayView2<f64>,
+    start: [usize; 2],
+    end: [usize; 2],
+    path_cost: MinimalPathCost,
+) -> Vec<[usize; 2]> {
+    let shape = [cost.nrows(), cost.ncols()];
+    let adj = AdjacencyGrid2D::with_connectivity(&shape, 8);
+
+    if start[0] >= shape[0] || start[1] >= shape[1] || end[0] >= shape[0] || end[1] >= shape[1] {
+        panic!("Path end points must be inside the image");
+    }
+
+    let cost = cost.to_shape(cost.len()).unwrap();
+    let mask = Array1::from_elem(cost.len(), true);
+
+    let source = start[0] * shape[1] + start[1];
+    let target = end[0] * shape[1] + end[1];
+    let seeds = vec![Seed::new(source, 0.0, 1)];
+
+    let weight = |p: usize, q: usize| cost[q] * squared_distance(&shape, &[1.0, 1.0], p, q).sqrt();
+
+    let forest = match path_cost {
+        MinimalPathCost::Additive => image_foresting_transform_with_target(
+            &adj,
+            &mut FSum::new(weight),
+            &seeds,
+            &mask.view(),
+            Some(target),
+        ),
+        MinimalPathCost::Riverbed => image_foresting_transform_with_target(
+            &adj,
+            &mut FLast::new(weight),
+            &seeds,
+            &mask.view(),
+            Some(target),
+        ),
+    };
+
+    if forest.roots[target] < 0 {
+        return Vec::new();
+    }
+
+    forest
+        .path(target)
+        .iter()
+        .map(|&p| [p / shape[1], p % shape[1]])
+        .collect()
+}
+
+#[test]
+fn test_minimal_path() {
+    // low cost valley going around the center
+    let mut cost = Array2::from_elem((5, 5), 10.0);
+    for (y, x) in [(0, 0), (1, 0), (2, 0), (3, 1), (4, 2), (3, 3), (2, 4)] {
+        cost[[y, x]] = 1.0;
+    }
+
+    let expected = vec![[0, 0], [1, 0], [2, 0], [3, 1], [4, 2], [3, 3], [2, 4]];
+    let path = minimal_path(&cost.view(), [0, 0], [2, 4], MinimalPathCost::Additive);
+    assert_eq!(path, expected);
+
+    let path = minimal_path(&cost.view(), [0, 0], [2, 4], MinimalPathCost::Riverbed);
+    assert_eq!(path, expected);
+
+    let path = minimal_path(&cost.view(), [3, 3], [3, 3], MinimalPathCost::Additive);
+    assert_eq!(path, vec![[3, 3]]);
+}
+
+#[pyfunction]
+pub fn minimal_path_2d<'py>(
+    py: Python<'py>,
+    cost: PyReadonlyArray2<f64>,
+    start: [usize; 2],
+    end: [usize; 2],
+    riverbed: bool,
+) -> PyResult<&'py PyArray2<usize>> {
+    let path_cost = if riverbed {
+        MinimalPathCost::Riverbed
+    } else {
+        MinimalPathCost::Additive
+    };
+    let path = minimal_path(&cost.as_array(), start, end, path_cost);
+    let coords = Array2::from_shape_vec((path.len(), 2), path.concat()).unwrap();
+    Ok(coords.into_pyarray(py))
+}
@@ -0,0 +1,357 @@
+// pyo3 0.19 #[pymethods] expansion defines impls inside functions
+#![allow(non_local_definitions)]
+
+use ndarray::*;
+use numpy::{IntoPyArray, PyArray1, PyReadonlyArray1, PyReadonlyArray2};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::collections::HashMap;
+
+use crate::adjacency::{Adjacency, CompleteGraph};
+use crate::ift::{image_foresting_transform, FMax, PathCost, Seed};
+use crate::priority_queue::Policy;
+
+#[inline(always)]
+fn euclidean(a: ArrayView1<f64>, b: ArrayView1<f64>) -> f64 {
+    a.iter()
+        .zip(b)
+        .map(|(&x, &y)| (x - y).powi(2))
+        .sum::<f64>()
+        .sqrt()
+}
+
+/// Samples on the minimum spanning tree edges between different classes.
+fn mst_prototypes(features: &ArrayView2<f64>, labels: &ArrayView1<usize>) -> Vec<usize> {
+    let n = features.nrows();
+    let mut in_tree = vec![false; n];
+    let mut best = vec![f64::INFINITY; n];
+    let mut parent: Vec<Option<usize>> = vec![None; n];
+    let mut is_prototype = vec![false; n];
+    best[0] = 0.0;
+
+    // Prim on the complete graph
+    for _ in 0..n {
+        let u = (0..n)
+            .filter(|&v| !in_tree[v])
+            .min_by(|&a, &b| best[a].total_cmp(&best[b]))
+            .unwrap();
+        in_tree[u] = true;
+        if let Some(v) = parent[u] {
+            if labels[u] != labels[v] {
+                is_prototype[u] = true;
+                is_prototype[v] = true;
+            }
+        }
+        for v in 0..n {
+            if !in_tree[v] {
+                let d = euclidean(features.row(u), features.row(v));
+                if d < best[v] {
+                    best[v] = d;
+                    parent[v] = Some(u);
+                }
+            }
+        }
+    }
+
+    let prototypes: Vec<usize> = (0..n).filter(|&i| is_prototype[i]).collect();
+    // a single class has no boundary, any sample represents it
+    if prototypes.is_empty() {
+        vec![0]
+    } else {
+        prototypes
+    }
+}
+
+/// Supervised optimum-path forest classifier, the training samples are conquered by the
+/// prototypes of their classes with fmax on the complete graph of Euclidean distances.
+pub struct OpfClassifier {
+    features: Array2<f64>,
+    costs: Array1<f64>,
+    labels: Array1<usize>,
+    // training samples by increasing cost
+    order: Vec<usize>,
+}
+
+impl OpfClassifier {
+    pub fn fit(features: &ArrayView2<f64>, labels: &ArrayView1<usize>) -> Self {
+        let n = features.nrows();
+        if n == 0 || labels.len() != n {
+            panic!("Features and labels must have the same non-zero number of samples");
+        }
+
+        let seeds: Vec<Seed<f64>> = mst_prototypes(features, labels)
+            .into_iter()
+            .map(|p| Seed::new(p, 0.0, labels[p]))
+            .collect();
+
+        let adj = CompleteGraph::new(n);
+        let mask = Array1::from_elem(n, true);
+        let mut path_cost = FMax::new(|p, q| euclidean(features.row(p), features.row(q)));
+        let forest = image_foresting_transform(&adj, &mut path_cost, &seeds, &mask.view());
+
+        let mut order: Vec<usize> = (0..n).collect();
+        order.sort_by(|&a, &b| forest.costs[a].total_cmp(&forest.costs[b]));
+
+        OpfClassifier {
+            features: features.to_owned(),
+            costs: forest.costs,
+            labels: forest.labels,
+            order,
+        }
+    }
+
+    /// Label of the training sample offering the optimum path to each sample.
+    pub fn predict(&self, features: &ArrayView2<f64>) -> Array1<usize> {
+        features
+            .rows()
+            .into_iter()
+            .map(|x| {
+                let mut best_cost = f64::INFINITY;
+                let mut best_label = 0;
+                for &s in &self.order {
+                    // the remaining samples cannot offer a cheaper path
+                    if self.costs[s] >= best_cost {
+                        break;
+                    }
+                    let cost = self.costs[s].max(euclidean(self.features.row(s), x));
+                    if cost < best_cost {
+                        best_cost = cost;
+                        best_label = self.labels[s];
+                    }
+                }
+                best_label
+            })
+            .collect()
+    }
+}
+
+/// Symmetric k-nearest neighbors graph.
+struct KnnGraph {
+    neighbors: Vec<Vec<usize>>,
+}
+
+impl Adjacency for KnnGraph {
+    fn neighbors(&self, p: usize) -> Vec<usize> {
+        self.neighbors[p].clone()
+    }
+}
+
+/// fmin on the pdf where the handicap of the trivial paths is removed once they root a cluster.
+struct FMinHMaxima<'a> {
+    pdf: &'a [f64],
+}
+
+impl<'a> PathCost<f64> for FMinHMaxima<'a> {
+    #[inline(always)]
+    fn extend(&self, _p: usize, q: usize, cost_p: f64, _root: usize) -> f64 {
+        cost_p.min(self.pdf[q])
+    }
+
+    fn policy(&self) -> Policy {
+        Policy::Max
+    }
+
+    fn root_cost(&self, p: usize, _cost: f64) -> f64 {
+        self.pdf[p]
+    }
+}
+
+/// Unsupervised optimum-path forest clustering result, `k` is the number of nearest neighbors
+/// minimizing the normalized cut.
+pub struct OpfClustering {
+    pub labels: Array1<usize>,
+    pub pdf: Array1<f64>,
+    pub k: usize,
+}
+
+/// Clusters of the `k`-nearest neighbors graph, `knn` has the neighbors of each sample by
+/// increasing distance, returns the labels from 1, the pdf and the normalized cut.
+fn cluster_knn(knn: &[Vec<(usize, f64)>], k: usize) -> (Array1<usize>, Array1<f64>, f64) {
+    let n = knn.len();
+    let arcs = |s: usize| knn[s].iter().take(k);
+
+    let max_distance = (0..n)
+        .flat_map(|s| arcs(s).map(|&(_, d)| d))
+        .fold(0.0, f64::max);
+    let sigma = if max_distance > 0.0 {
+        max_distance / 3.0
+    } else {
+        1.0
+    };
+
+    let pdf: Vec<f64> = (0..n)
+        .map(|s| {
+            arcs(s)
+                .map(|&(_, d)| (-d * d / (2.0 * sigma * sigma)).exp())
+                .sum::<f64>()
+                / (k as f64 * (2.0 * std::f64::consts::PI).sqrt() * sigma)
+        })
+        .collect();
+
+    // the handicap is smaller than any difference between pdf values
+    let mut sorted = pdf.clone();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let delta = sorted
+        .windows(2)
+        .map(|w| w[1] - w[0])
+        .filter(|&d| d > 0.0)
+        .fold(f64::INFINITY, f64::min);
+    let delta = if delta.is_finite() { delta / 2.0 } else { 1.0 };
+
+    let mut neighbors: Vec<Vec<usize>> =
+        (0..n).map(|s| arcs(s).map(|&(t, _)| t).collect()).collect();
+    for s in 0..n {
+        for &(t, _) in arcs(s) {
+            if !neighbors[t].contains(&s) {
+                neighbors[t].push(s);
+            }
+        }
+    }
+
+    let seeds: Vec<Seed<f64>> = (0..n)
+        .map(|s| Seed::new(s, pdf[s] - delta, s + 1))
+        .collect();
+    let mask = Array1::from_elem(n, true);
+    let forest = image_foresting_transform(
+        &KnnGraph { neighbors },
+        &mut FMinHMaxima { pdf: &pdf },
+        &seeds,
+        &mask.view(),
+    );
+
+    let mut relabel = HashMap::new();
+    let labels = forest.labels.mapv(|l| {
+        let next = relabel.len() + 1;
+        *relabel.entry(l).or_insert(next)
+    });
+
+    let n_clusters = relabel.len();
+    let mut inside = vec![0.0; n_clusters];
+    let mut cut = vec![0.0; n_clusters];
+    for s in 0..n {
+        let c = labels[s] - 1;
+        for &(t, d) in arcs(s) {
+            let w = 1.0 / d.max(1e-12);
+            if labels[t] == labels[s] {
+                inside[c] += w;
+            } else {
+                cut[c] += w;
+            }
+        }
+    }
+    let normalized_cut = (0..n_clusters)
+        .filter(|&c| inside[c] + cut[c] > 0.0)
+        .map(|c| cut[c] / (inside[c] + cut[c]))
+        .sum();
+
+    (labels, Array1::from(pdf), normalized_cut)
+}
+
+/// Unsupervised optimum-path forest clustering, each maximum of the pdf estimated on the
+/// k-nearest neighbors graph roots a cluster, with k in `1..=k_max` minimizing the normalized cut.
+/// The neighbors are found by brute force in O(N² log N) for N samples.
+pub fn opf_clustering(features: &ArrayView2<f64>, k_max: usize) -> OpfClustering {
+    let n = features.nrows();
+    if n < 2 {
+        panic!("At least two samples are required");
+    }
+    let k_max = k_max.clamp(1, n - 1);
+
+    let knn: Vec<Vec<(usize, f64)>> = (0..n)
+        .map(|s| {
+            let mut distances: Vec<(usize, f64)> = (0..n)
+                .filter(|&t| t != s)
+                .map(|t| (t, euclidean(features.row(s), features.row(t))))
+                .collect();
+            distances.sort_by(|a, b| a.1.total_cmp(&b.1));
+            distances.truncate(k_max);
+            distances
+        })
+        .collect();
+
+    let mut best: Option<OpfClustering> = None;
+    let mut best_cut = f64::INFINITY;
+    for k in 1..=k_max {
+        let (labels, pdf, normalized_cut) = cluster_knn(&knn, k);
+        if normalized_cut < best_cut {
+            best_cut = normalized_cut;
+            best = Some(OpfClustering { labels, pdf, k });
+        }
+    }
+    best.unwrap()
+}
+
+#[test]
+fn test_opf_classifier() {
+    let features = array![[0.0], [1.0], [2.0], [10.0], [11.0]];
+    let labels = array![0, 0, 0, 1, 1];
+
+    assert_eq!(mst_prototypes(&features.view(), &labels.view()), vec![2, 3]);
+
+    let opf = OpfClassifier::fit(&features.view(), &labels.view());
+    assert_eq!(opf.costs, array![1.0, 1.0, 0.0, 0.0, 1.0]);
+    assert_eq!(opf.predict(&features.view()), labels);
+    assert_eq!(
+        opf.predict(&array![[4.0], [8.0], [-5.0], [20.0]].view()),
+        array![0, 1, 0, 1]
+    );
+}
+
+#[test]
+fn test_opf_clustering() {
+    let cluster = array![[0.0, 0.0], [0.0, 1.0], [1.0, 0.0], [1.0, 1.0], [0.5, 0.5]];
+    let features = concatenate![Axis(0), cluster, &cluster + 10.0];
+    let clustering = opf_clustering(&features.view(), 3);
+    assert_eq!(clustering.labels, array![1, 1, 1, 1, 1, 2, 2, 2, 2, 2]);
+    assert_eq!(clustering.pdf.len(), 10);
+}
+
+#[pyclass]
+pub struct OpfClassifierModel {
+    opf: OpfClassifier,
+}
+
+#[pymethods]
+impl OpfClassifierModel {
+    #[new]
+    fn new(features: PyReadonlyArray2<f64>, labels: PyReadonlyArray1<usize>) -> PyResult<Self> {
+        if features.shape()[0] == 0 || features.shape()[0] != labels.len() {
+            return Err(PyValueError::new_err(
+                "Features and labels must have the same non-zero number of samples.",
+            ));
+        }
+        Ok(OpfClassifierModel {
+            opf: OpfClassifier::fit(&features.as_array(), &labels.as_array()),
+        })
+    }
+
+    fn predict<'py>(
+        &self,
+        py: Python<'py>,
+        features: PyReadonlyArray2<f64>,
+    ) -> PyResult<&'py PyArray1<usize>> {
+        if features.shape()[1] != self.opf.features.ncols() {
+            return Err(PyValueError::new_err(
+                "Features must have the same number of columns as the training features.",
+            ));
+        }
+        Ok(self.opf.predict(&features.as_array()).into_pyarray(py))
+    }
+}
+
+#[pyfunction]
+pub fn opf_clustering_samples<'py>(
+    py: Python<'py>,
+    features: PyReadonlyArray2<f64>,
+    k_max: usize,
+) -> PyResult<(&'py PyArray1<usize>, &'py PyArray1<f64>, usize)> {
+    if features.shape()[0] < 2 {
+        return Err(PyValueError::new_err("At least two samples are required."));
+    }
+    let clustering = opf_clustering(&features.as_array(), k_max);
+    Ok((
+        clustering.labels.into_pyarray(py),
+        clustering.pdf.into_pyarray(py),
+        clustering.k,
+    ))
+}
@@ -0,0 +1,141 @@
+use ndarray::*;
+use numpy::PyReadonlyArray;
+use pyo3::prelude::*;
+
+use crate::adjacency::grid_adjacency;
+use crate::forest::{OptimumPathForest, PyForest};
+use crate::ift::{image_foresting_transform, PathCost, Seed};
+
+/// fmax of the oriented arc weights, w(p, q) = |I(q) - I(p)| is scaled by (1 + α) on bright to dark
+/// transitions of object trees and on dark to bright transitions of background trees, and by (1 - α)
+/// on the opposite ones. Positive α favors boundaries where the object is brighter than the background.
+pub struct OrientedCost<'a> {
+    image: ArrayView1<'a, f64>,
+    markers: ArrayView1<'a, usize>,
+    background_label: usize,
+    alpha: f64,
+}
+
+impl<'a> OrientedCost<'a> {
+    pub fn new(
+        image: ArrayView1<'a, f64>,
+        markers: ArrayView1<'a, usize>,
+        background_label: usize,
+        alpha: f64,
+    ) -> Self {
+        if !(-1.0..=1.0).contains(&alpha) {
+            panic!("alpha must be in [-1, 1]");
+        }
+        OrientedCost {
+            image,
+            markers,
+            background_label,
+            alpha,
+        }
+    }
+
+    #[inline(always)]
+    fn weight(&self, p: usize, q: usize, root: usize) -> f64 {
+        let diff = self.image[q] - self.image[p];
+        let weight = diff.abs();
+        let is_object = self.markers[root] != self.background_label;
+        if diff == 0.0 {
+            weight
+        } else if (diff < 0.0) == is_object {
+            weight * (1.0 + self.alpha)
+        } else {
+            weight * (1.0 - self.alpha)
+        }
+    }
+}
+
+impl<'a> PathCost<f64> for OrientedCost<'a> {
+    #[inline(always)]
+    fn extend(&self, p: usize, q: usize, cost_p: f64, root: usize) -> f64 {
+        cost_p.max(self.weight(p, q, root))
+    }
+}
+
+/// Oriented watershed from `markers`, trees rooted at `background_label` markers are background and
+/// the remaining ones are objects, `alpha` in [-1, 1] sets the boundary polarity.
+pub fn oriented_watershed<D>(
+    image: &ArrayView<f64, D>,
+    markers: &ArrayView<usize, D>,
+    mask: &ArrayView<bool, D>,
+    background_label: usize,
+    alpha: f64,
+) -> OptimumPathForest<f64, D>
+where
+    D: Dimension,
+{
+    let shape = image.raw_dim();
+    let adj = grid_adjacency(image.shape());
+
+    let image = image.to_shape(image.len()).unwrap();
+    let markers = markers.to_shape(markers.len()).unwrap();
+    let mask = mask.to_shape(mask.len()).unwrap();
+
+    let seeds: Vec<Seed<f64>> = (0..image.len())
+        .filter(|&i| mask[i] && markers[i] != 0)
+        .map(|i| Seed::new(i, 0.0, markers[i]))
+        .collect();
+
+    let mut path_cost = OrientedCost::new(image.view(), markers.view(), background_label, alpha);
+
+    image_foresting_transform(adj.as_ref(), &mut path_cost, &seeds, &mask.view()).into_shape(shape)
+}
+
+#[test]
+fn test_oriented_watershed() {
+    let image = array![0.0, 2.0];
+    let markers = array![1, 2];
+    let path_cost = OrientedCost::new(image.view(), markers.view(), 1, 0.5);
+
+    // background: dark to bright is penalized
+    assert_eq!(path_cost.weight(0, 1, 0), 3.0);
+    assert_eq!(path_cost.weight(1, 0, 0), 1.0);
+    // object: bright to dark is penalized
+    assert_eq!(path_cost.weight(1, 0, 1), 3.0);
+    assert_eq!(path_cost.weight(0, 1, 1), 1.0);
+
+    // the dark valley between the bright object and the brighter background on the right
+    // belongs to the background once boundary polarity is enforced
+    let image = array![[2.0, 2.0, 6.0, 6.0, 3.0, 9.0, 9.0]];
+    let mut markers = Array2::<usize>::zeros(image.dim());
+    markers[[0, 0]] = 1;
+    markers[[0, 3]] = 2;
+    markers[[0, 6]] = 1;
+    let mask = Array2::from_elem(image.dim(), true);
+
+    let forest = oriented_watershed(&image.view(), &markers.view(), &mask.view(), 1, 0.0);
+    assert_eq!(forest.labels, array![[1, 1, 2, 2, 2, 1, 1]]);
+
+    let forest = oriented_watershed(&image.view(), &markers.view(), &mask.view(), 1, 0.8);
+    assert_eq!(forest.labels, array![[1, 1, 2, 2, 1, 1, 1]]);
+}
+
+macro_rules! impl_oriented_watershed {
+    ($new_name:ident, $dim:expr) => {
+        #[pyfunction]
+        pub fn $new_name<'py>(
+            py: Python<'py>,
+            image: PyReadonlyArray<f64, Dim<[usize; $dim]>>,
+            markers: PyReadonlyArray<usize, Dim<[usize; $dim]>>,
+            mask: PyReadonlyArray<bool, Dim<[usize; $dim]>>,
+            background_label: usize,
+            alpha: f64,
+        ) -> PyResult<PyForest<'py, f64, Dim<[usize; $dim]>>> {
+            let forest = oriented_watershed(
+                &image.as_array(),
+                &markers.as_array(),
+                &mask.as_array(),
+                background_label,
+                alpha,
+            );
+            Ok(forest.into_pyarrays(py))
+        }
+    };
+}
+
+impl_oriented_watershed!(oriented_watershed_2d, 2);
+impl_oriented_watershed!(oriented_watershed_3d, 3);
@@ -5,6 +5,12 @@ pub enum ElemStatus {
     POPPED,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Policy {
+    Min,
+    Max,
+}
+
 pub trait PriorityQueue<'a, T: 'static> {
     fn is_full(&self) -> bool;
     fn is_empty(&mut self) -> bool;
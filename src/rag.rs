@@ -0,0 +1,212 @@
+use ndarray::*;
+use numpy::{IntoPyArray, PyArray1, PyArray2, PyReadonlyArray};
+use pyo3::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::adjacency::grid_adjacency_with_connectivity;
+
+/// Mean, minimum and maximum of a set of values.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Statistics {
+    pub sum: f64,
+    pub min: f64,
+    pub max: f64,
+    pub count: usize,
+}
+
+impl Statistics {
+    fn new() -> Self {
+        Statistics {
+            sum: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            count: 0,
+        }
+    }
+
+    fn push(&mut self, value: f64) {
+        self.sum += value;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        self.count += 1;
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.sum / self.count as f64
+    }
+}
+
+/// Region adjacency graph of a label image, label 0 is background and has no node.
+/// Nodes are sorted by label and edges by their (smaller, larger) node indices.
+pub struct RegionAdjacencyGraph {
+    /// label of each node
+    pub labels: Vec<usize>,
+    pub area: Vec<usize>,
+    /// mean coordinates of each node
+    pub centroids: Array2<f64>,
+    /// node indices of each edge, the smaller one first
+    pub edges: Vec<(usize, usize)>,
+    /// number of pairs of adjacent pixels between the two regions
+    pub boundary_length: Vec<usize>,
+    /// weights of the pixels of each node
+    pub node_weights: Option<Vec<Statistics>>,
+    /// maximum weight of each pair of adjacent pixels along the boundary of each edge
+    pub edge_weights: Option<Vec<Statistics>>,
+}
+
+impl RegionAdjacencyGraph {
+    pub fn new<D: Dimension>(
+        labels: &ArrayView<usize, D>,
+        weights: Option<&ArrayView<f64, D>>,
+        connectivity: usize,
+    ) -> Self {
+        let shape = labels.shape().to_vec();
+        if weights.is_some_and(|w| w.shape() != shape.as_slice()) {
+            panic!("Labels and weights must have the same shape");
+        }
+        let adj = grid_adjacency_with_connectivity(&shape, connectivity);
+
+        let size = labels.len();
+        let ndim = shape.len();
+        let flat_labels = labels.to_shape(size).unwrap();
+        let weights = weights.map(|w| w.to_shape(size).unwrap());
+
+        let mut node_labels: Vec<usize> = flat_labels.iter().copied().filter(|&l| l > 0).collect();
+        node_labels.sort_unstable();
+        node_labels.dedup();
+        let node: HashMap<usize, usize> = node_labels
+            .iter()
+            .enumerate()
+            .map(|(i, &l)| (l, i))
+            .collect();
+
+        let n_nodes = node_labels.len();
+        let mut area = vec![0; n_nodes];
+        let mut centroids = Array2::zeros((n_nodes, ndim));
+        let mut node_weights = vec![Statistics::new(); n_nodes];
+        let mut boundaries: BTreeMap<(usize, usize), (usize, Statistics)> = BTreeMap::new();
+
+        for p in 0..size {
+            if flat_labels[p] == 0 {
+                continue;
+            }
+            let a = node[&flat_labels[p]];
+            area[a] += 1;
+            let mut index = p;
+            for d in (0..ndim).rev() {
+                centroids[[a, d]] += (index % shape[d]) as f64;
+                index /= shape[d];
+            }
+            if let Some(w) = &weights {
+                node_weights[a].push(w[p]);
+            }
+
+            for q in adj.neighbors(p) {
+                if q < p || flat_labels[q] == 0 || flat_labels[q] == flat_labels[p] {
+                    continue;
+                }
+                let b = node[&flat_labels[q]];
+                let (length, stats) = boundaries
+                    .entry((a.min(b), a.max(b)))
+                    .or_insert((0, Statistics::new()));
+                *length += 1;
+                if let Some(w) = &weights {
+                    stats.push(w[p].max(w[q]));
+                }
+            }
+        }
+
+        for (a, &count) in area.iter().enumerate() {
+            centroids.row_mut(a).mapv_inplace(|x| x / count as f64);
+        }
+
+        let has_weights = weights.is_some();
+        RegionAdjacencyGraph {
+            labels: node_labels,
+            area,
+            centroids,
+            edges: boundaries.keys().copied().collect(),
+            boundary_length: boundaries.values().map(|&(length, _)| length).collect(),
+            node_weights: has_weights.then_some(node_weights),
+            edge_weights: has_weights.then(|| boundaries.values().map(|&(_, s)| s).collect()),
+        }
+    }
+}
+
+/// (n, 3) array with the mean, minimum and maximum of each statistics.
+fn statistics_array(stats: &[Statistics]) -> Array2<f64> {
+    Array2::from_shape_fn((stats.len(), 3), |(i, j)| match j {
+        0 => stats[i].mean(),
+        1 => stats[i].min,
+        _ => stats[i].max,
+    })
+}
+
+#[test]
+fn test_region_adjacency_graph() {
+    let labels = array![[1, 1, 3, 3], [1, 1, 3, 3], [0, 7, 7, 7]];
+    let weights = array![
+        [0.0, 1.0, 2.0, 0.0],
+        [0.0, 4.0, 6.0, 0.0],
+        [9.0, 3.0, 3.0, 1.0]
+    ];
+
+    let rag = RegionAdjacencyGraph::new(&labels.view(), None, 4);
+    assert_eq!(rag.labels, vec![1, 3, 7]);
+    assert_eq!(rag.area, vec![4, 4, 3]);
+    assert_eq!(rag.centroids.row(2), array![2.0, 2.0]);
+    assert_eq!(rag.edges, vec![(0, 1), (0, 2), (1, 2)]);
+    assert_eq!(rag.boundary_length, vec![2, 1, 2]);
+    assert!(rag.node_weights.is_none() && rag.edge_weights.is_none());
+
+    let rag = RegionAdjacencyGraph::new(&labels.view(), Some(&weights.view()), 8);
+    assert_eq!(rag.edges, vec![(0, 1), (0, 2), (1, 2)]);
+    assert_eq!(rag.boundary_length, vec![4, 3, 5]);
+    let node_weights = rag.node_weights.unwrap();
+    assert_eq!(node_weights[0].mean(), 1.25);
+    assert_eq!((node_weights[2].min, node_weights[2].max), (1.0, 3.0));
+    let edge_weights = statistics_array(&rag.edge_weights.unwrap());
+    assert_eq!(edge_weights.row(0), array![4.5, 2.0, 6.0]);
+}
+
+type PyRag<'py> = (
+    &'py PyArray1<usize>,
+    &'py PyArray1<usize>,
+    &'py PyArray2<f64>,
+    &'py PyArray2<usize>,
+    &'py PyArray1<usize>,
+    Option<&'py PyArray2<f64>>,
+    Option<&'py PyArray2<f64>>,
+);
+
+macro_rules! impl_region_adjacency_graph {
+    ($new_name:ident, $dim:expr) => {
+        #[pyfunction]
+        pub fn $new_name<'py>(
+            py: Python<'py>,
+            labels: PyReadonlyArray<usize, Dim<[usize; $dim]>>,
+            connectivity: usize,
+            weights: Option<PyReadonlyArray<f64, Dim<[usize; $dim]>>>,
+        ) -> PyResult<PyRag<'py>> {
+            let weights = weights.as_ref().map(|w| w.as_array());
+            let rag = RegionAdjacencyGraph::new(&labels.as_array(), weights.as_ref(), connectivity);
+            let edges: Vec<usize> = rag.edges.iter().flat_map(|&(a, b)| [a, b]).collect();
+            Ok((
+                PyArray1::from_vec(py, rag.labels),
+                PyArray1::from_vec(py, rag.area),
+                rag.centroids.into_pyarray(py),
+                Array2::from_shape_vec((edges.len() / 2, 2), edges)
+                    .unwrap()
+                    .into_pyarray(py),
+                PyArray1::from_vec(py, rag.boundary_length),
+                rag.node_weights
+                    .map(|s| statistics_array(&s).into_pyarray(py)),
+                rag.edge_weights
+                    .map(|s| statistics_array(&s).into_pyarray(py)),
+            ))
+        }
+    };
+}
+
+impl_region_adjacency_graph!(region_adjacency_graph_2d, 2);
+impl_region_adjacency_graph!(region_adjacency_graph_3d, 3);
@@ -0,0 +1,117 @@
+use ndarray::*;
+use num_traits::{Bounded, ToPrimitive, Zero};
+use numpy::{Element, IntoPyArray, PyArray, PyReadonlyArray};
+use pyo3::prelude::*;
+use std::ops::Sub;
+
+use crate::adjacency::grid_adjacency;
+use crate::ift::{image_foresting_transform, FMax, FMin, PathCost, Seed};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReconstructionMethod {
+    Dilation,
+    Erosion,
+}
+
+/// Grayscale morphological reconstruction of `marker` under (dilation) or over (erosion) `mask`.
+/// The marker is clipped to the mask, every pixel is a seed with its marker value and paths are
+/// constrained by the mask values with fmin (maximized) for dilation and fmax for erosion.
+pub fn reconstruction<T, D>(
+    marker: &ArrayView<T, D>,
+    mask: &ArrayView<T, D>,
+    method: ReconstructionMethod,
+) -> Array<T, D>
+where
+    T: Bounded + Copy + Element + PartialOrd + Sub<Output = T> + ToPrimitive + Zero + 'static,
+    D: Dimension,
+{
+    let shape = marker.raw_dim();
+    let adj = grid_adjacency(marker.shape());
+
+    if marker.shape() != mask.shape() {
+        panic!("Marker and mask must have the same shape");
+    }
+
+    let marker = marker.to_shape(marker.len()).unwrap();
+    let mask = mask.to_shape(mask.len()).unwrap();
+    let domain = Array1::from_elem(marker.len(), true);
+
+    let clip = |i: usize| match method {
+        ReconstructionMethod::Dilation if marker[i] > mask[i] => mask[i],
+        ReconstructionMethod::Erosion if marker[i] < mask[i] => mask[i],
+        _ => marker[i],
+    };
+
+    let seeds: Vec<Seed<T>> = (0..marker.len())
+        .map(|i| Seed::new(i, clip(i), 1))
+        .collect();
+
+    let weight = |_, q: usize| mask[q];
+    let mut path_cost: Box<dyn PathCost<T>> = match method {
+        ReconstructionMethod::Dilation => Box::new(FMin::new(weight)),
+        ReconstructionMethod::Erosion => Box::new(FMax::new(weight)),
+    };
+
+    let forest =
+        image_foresting_transform(adj.as_ref(), path_cost.as_mut(), &seeds, &domain.view());
+
+    forest.costs.into_shape(shape).unwrap()
+}
+
+#[test]
+fn test_reconstruction() {
+    let mask = array![[1, 3, 2, 5, 2, 1]];
+
+    let marker = array![[0, 2, 1, 4, 1, 0]];
+    let result = reconstruction(&marker.view(), &mask.view(), ReconstructionMethod::Dilation);
+    assert_eq!(result, array![[1, 2, 2, 4, 2, 1]]);
+
+    let marker = array![[5, 5, 5, 5, 5, 1]];
+    let result = reconstruction(&marker.view(), &mask.view(), ReconstructionMethod::Erosion);
+    assert_eq!(result, array![[5, 5, 5, 5, 2, 1]]);
+
+    // signed values use the heap
+    let mask = array![[-1, 3], [-4, 1]];
+    let marker = array![[-2, -2], [-5, -5]];
+    let result = reconstruction(&marker.view(), &mask.view(), ReconstructionMethod::Dilation);
+    assert_eq!(result, array![[-2, -2], [-4, -2]]);
+}
+
+macro_rules! impl_reconstruction {
+    ($new_name:ident, $ty:ty, $dim:expr) => {
+        #[pyfunction]
+        pub fn $new_name<'py>(
+            py: Python<'py>,
+            marker: PyReadonlyArray<$ty, Dim<[usize; $dim]>>,
+            mask: PyReadonlyArray<$ty, Dim<[usize; $dim]>>,
+            dilation: bool,
+        ) -> PyResult<&'py PyArray<$ty, Dim<[usize; $dim]>>> {
+            let method = if dilation {
+                ReconstructionMethod::Dilation
+            } else {
+                ReconstructionMethod::Erosion
+            };
+            let arr = reconstruction(&marker.as_array(), &mask.as_array(), method);
+            Ok(arr.into_pyarray(py))
+        }
+    };
+}
+
+impl_reconstruction!(reconstruction_u8_2d, u8, 2);
+impl_reconstruction!(reconstruction_u8_3d, u8, 3);
+impl_reconstruction!(reconstruction_u16_2d, u16, 2);
+impl_reconstruction!(reconstruction_u16_3d, u16, 3);
+impl_reconstruction!(reconstruction_u32_2d, u32, 2);
+impl_reconstruction!(reconstruction_u32_3d, u32, 3);
+
+impl_reconstruction!(reconstruction_i16_2d, i16, 2);
+impl_reconstruction!(reconstruction_i16_3d, i16, 3);
+impl_reconstruction!(reconstruction_i32_2d, i32, 2);
+impl_reconstruction!(reconstruction_i32_3d, i32, 3);
+impl_reconstruction!(reconstruction_i64_2d, i64, 2);
+impl_reconstruction!(reconstruction_i64_3d, i64, 3);
+
+impl_reconstruction!(reconstruction_f32_2d, f32, 2);
+impl_reconstruction!(reconstruction_f32_3d, f32, 3);
+impl_reconstruction!(reconstruction_f64_2d, f64, 2);
+impl_reconstruction!(reconstruction_f64_3d, f64, 3);
@@ -0,0 +1,345 @@
+use ndarray::*;
+use numpy::{IntoPyArray, PyArray, PyReadonlyArray};
+use pyo3::prelude::*;
+
+use crate::adjacency::grid_adjacency;
+use crate::dynamic_trees::DynamicTreeCost;
+use crate::ift::{image_foresting_transform, PathCost, Seed};
+use std::collections::HashSet;
+
+/// fsum where the arc weight w(p, q) is the distance between the features of `q` and the
+/// features of the seed of `p` plus `compactness` for each step.
+pub struct SuperpixelCost<'a> {
+    features: ArrayView2<'a, f64>,
+    seed_features: Array2<f64>,
+    // seed of each root node
+    seed: Vec<usize>,
+    compactness: f64,
+}
+
+impl<'a> SuperpixelCost<'a> {
+    pub fn new(
+        features: ArrayView2<'a, f64>,
+        roots: &[usize],
+        seed_features: Array2<f64>,
+        compactness: f64,
+    ) -> Self {
+        let mut seed = vec![0; features.nrows()];
+        for (i, &r) in roots.iter().enumerate() {
+            seed[r] = i;
+        }
+        SuperpixelCost {
+            features,
+            seed_features,
+            seed,
+            compactness,
+        }
+    }
+}
+
+impl<'a> PathCost<f64> for SuperpixelCost<'a> {
+    #[inline(always)]
+    fn extend(&self, _p: usize, q: usize, cost_p: f64, root: usize) -> f64 {
+        let distance = self
+            .features
+            .row(q)
+            .iter()
+            .zip(self.seed_features.row(self.seed[root]))
+            .map(|(&x, &s)| (x - s).powi(2))
+            .sum::<f64>()
+            .sqrt();
+        cost_p + distance + self.compactness
+    }
+}
+
+/// Flat indices of about `n_seeds` seeds at the centers of the cells of a regular grid.
+/// Axes shorter than half a grid step get a single seed at their center and the step is
+/// recomputed on the remaining axes.
+pub fn grid_seeds(shape: &[usize], n_seeds: usize) -> Vec<usize> {
+    let mut thin = vec![false; shape.len()];
+    let step = loop {
+        let size: usize = (0..shape.len())
+            .filter(|&d| !thin[d])
+            .map(|d| shape[d])
+            .product();
+        let ndim = thin.iter().filter(|&&t| !t).count().max(1);
+        let step = (size as f64 / n_seeds.max(1) as f64)
+            .powf(1.0 / ndim as f64)
+            .max(1.0);
+        let too_short: Vec<usize> = (0..shape.len())
+            .filter(|&d| !thin[d] && (step / 2.0) as usize >= shape[d])
+            .collect();
+        if too_short.is_empty() {
+            break step;
+        }
+        too_short.into_iter().for_each(|d| thin[d] = true);
+    };
+
+    let mut seeds = vec![0];
+    for (&s, &is_thin) in shape.iter().zip(&thin) {
+        let positions: Vec<usize> = if is_thin {
+            vec![s / 2]
+        } else {
+            (0..)
+                .map(|k| (step / 2.0 + k as f64 * step) as usize)
+                .take_while(|&x| x < s)
+                .collect()
+        };
+        seeds = seeds
+            .iter()
+            .flat_map(|&i| positions.iter().map(move |&x| i * s + x))
+            .collect();
+    }
+    seeds
+}
+
+/// Spatial shape of an image with the channels on its last axis.
+pub(crate) fn spatial_dim<D: Dimension>(shape: &[usize]) -> D {
+    let mut dim = D::zeros(shape.len() - 1);
+    for (d, &s) in shape[..shape.len() - 1].iter().enumerate() {
+        dim[d] = s;
+    }
+    dim
+}
+
+/// Superpixels by the iterative spanning forest (ISF). The IFT is run from grid seeds and each
+/// seed is moved to the pixel of its tree closest to the tree centroid, until the seeds converge
+/// or `n_iterations` is reached. `image` has the channels on its last axis.
+pub fn isf_superpixels<D>(
+    image: &ArrayView<f64, D::Larger>,
+    n_superpixels: usize,
+    compactness: f64,
+    n_iterations: usize,
+) -> Array<usize, D>
+where
+    D: Dimension,
+{
+    let shape: D = spatial_dim(image.shape());
+    let spatial = shape.slice().to_vec();
+    let adj = grid_adjacency(&spatial);
+
+    let size = shape.size();
+    let n_channels = image.shape()[spatial.len()];
+    let features = image.to_shape((size, n_channels)).unwrap();
+    let mask = Array1::from_elem(size, true);
+
+    let coords: Array2<f64> = Array2::from_shape_fn((size, spatial.len()), |(p, d)| {
+        let stride: usize = spatial[d + 1..].iter().product();
+        ((p / stride) % spatial[d]) as f64
+    });
+
+    let mut roots = grid_seeds(&spatial, n_superpixels);
+    let mut seed_features = features.select(Axis(0), &roots);
+    let mut labels = Array1::zeros(size);
+
+    for _ in 0..n_iterations.max(1) {
+        let seeds: Vec<Seed<f64>> = roots
+            .iter()
+            .enumerate()
+            .map(|(i, &r)| Seed::new(r, 0.0, i + 1))
+            .collect();
+
+        let mut path_cost =
+            SuperpixelCost::new(features.view(), &roots, seed_features, compactness);
+        labels =
+            image_foresting_transform(adj.as_ref(), &mut path_cost, &seeds, &mask.view()).labels;
+
+        let n_trees = roots.len();
+        let mut counts = vec![0.0; n_trees];
+        let mut centroids = Array2::<f64>::zeros((n_trees, spatial.len()));
+        seed_features = Array2::zeros((n_trees, n_channels));
+        for p in 0..size {
+            let t = labels[p] - 1;
+            counts[t] += 1.0;
+            centroids.row_mut(t).scaled_add(1.0, &coords.row(p));
+            seed_features.row_mut(t).scaled_add(1.0, &features.row(p));
+        }
+        for (t, &count) in counts.iter().enumerate() {
+            centroids.row_mut(t).mapv_inplace(|x| x / count);
+            seed_features.row_mut(t).mapv_inplace(|x| x / count);
+        }
+
+        let mut new_roots = roots.clone();
+        let mut best = vec![f64::INFINITY; n_trees];
+        for p in 0..size {
+            let t = labels[p] - 1;
+            let distance: f64 = (&coords.row(p) - &centroids.row(t))
+                .iter()
+                .map(|x| x * x)
+                .sum();
+            if distance < best[t] {
+                best[t] = distance;
+                new_roots[t] = p;
+            }
+        }
+
+        if new_roots == roots {
+            break;
+        }
+        roots = new_roots;
+    }
+
+    labels.into_shape(shape).unwrap()
+}
+
+/// Superpixels by the dynamic and iterative spanning forest (DISF). The dynamic-tree IFT is run
+/// from `n_initial_seeds` grid seeds and only the most relevant seeds are kept for the next
+/// iteration, where the relevance of a tree is its relative size times the smallest distance
+/// between its mean features and those of its adjacent trees, until `n_superpixels` remain.
+pub fn disf_superpixels<D>(
+    image: &ArrayView<f64, D::Larger>,
+    n_superpixels: usize,
+    n_initial_seeds: usize,
+) -> Array<usize, D>
+where
+    D: Dimension,
+{
+    let shape: D = spatial_dim(image.shape());
+    let spatial = shape.slice().to_vec();
+    let adj = grid_adjacency(&spatial);
+
+    let size = shape.size();
+    let n_channels = image.shape()[spatial.len()];
+    let features = image.to_shape((size, n_channels)).unwrap();
+    let mask = Array1::from_elem(size, true);
+
+    let mut roots = grid_seeds(&spatial, n_initial_seeds.max(n_superpixels));
+    let n_initial = roots.len() as f64;
+    let mut iteration = 1;
+
+    loop {
+        let seeds: Vec<Seed<f64>> = roots
+            .iter()
+            .enumerate()
+            .map(|(i, &r)| Seed::new(r, 0.0, i + 1))
+            .collect();
+
+        let mut path_cost = DynamicTreeCost::new(features.view(), &roots);
+        let labels =
+            image_foresting_transform(adj.as_ref(), &mut path_cost, &seeds, &mask.view()).labels;
+
+        let n_kept = ((n_initial * (-(iteration as f64)).exp()) as usize).max(n_superpixels);
+        if roots.len() <= n_kept {
+            return labels.into_shape(shape).unwrap();
+        }
+
+        let n_trees = roots.len();
+        let mut adjacent = HashSet::new();
+        let mut counts = vec![0; n_trees];
+        for p in 0..size {
+            let t = labels[p] - 1;
+            counts[t] += 1;
+            for q in adj.neighbors(p) {
+                let u = labels[q] - 1;
+                if t != u {
+                    adjacent.insert((t, u));
+                }
+            }
+        }
+
+        let mut contrast = vec![f64::INFINITY; n_trees];
+        for &(t, u) in &adjacent {
+            let distance = (&path_cost.mean(roots[t]) - &path_cost.mean(roots[u]))
+                .iter()
+                .map(|x| x * x)
+                .sum::<f64>()
+                .sqrt();
+            contrast[t] = contrast[t].min(distance);
+        }
+
+        let relevance: Vec<f64> = (0..n_trees)
+            .map(|t| counts[t] as f64 / size as f64 * contrast[t])
+            .collect();
+
+        let mut order: Vec<usize> = (0..n_trees).collect();
+        order.sort_by(|&t, &u| relevance[u].total_cmp(&relevance[t]));
+        order.truncate(n_kept);
+        order.sort();
+
+        roots = order.iter().map(|&t| roots[t]).collect();
+        iteration += 1;
+    }
+}
+
+#[test]
+fn test_isf_superpixels() {
+    assert_eq!(grid_seeds(&[6, 8], 2), vec![2 * 8 + 2, 2 * 8 + 7]);
+    assert_eq!(grid_seeds(&[4, 4, 4], 8).len(), 8);
+    assert_eq!(grid_seeds(&[2, 1000], 1), vec![1000 + 500]);
+    assert_eq!(grid_seeds(&[10, 512, 512], 100).len(), 100);
+
+    let image = Array3::from_shape_fn(
+        (6, 8, 2),
+        |(_, x, c)| {
+            if x < 4 {
+                0.0
+            } else {
+                1.0 + c as f64
+            }
+        },
+    );
+    let labels: Array2<usize> = isf_superpixels(&image.view(), 2, 0.01, 10);
+
+    let mut expected = Array2::from_elem((6, 8), 1);
+    expected.slice_mut(s![.., 4..]).fill(2);
+    assert_eq!(labels, expected);
+
+    // thin image where the grid step is longer than the first axis
+    let thin = Array3::from_elem((2, 50, 1), 0.5);
+    let labels: Array2<usize> = isf_superpixels(&thin.view(), 1, 1.0, 2);
+    assert_eq!(labels, Array2::from_elem((2, 50), 1));
+}
+
+#[test]
+fn test_disf_superpixels() {
+    // the trees inside the wide region touch similar trees, so they are the least relevant ones
+    let image = Array3::from_shape_fn((4, 12, 1), |(_, x, _)| if x < 8 { 0.0 } else { 1.0 });
+    let labels: Array2<usize> = disf_superpixels(&image.view(), 2, 6);
+    let mut expected = Array2::from_elem((4, 12), 1);
+    expected.slice_mut(s![.., 8..]).fill(2);
+    assert_eq!(labels, expected);
+
+    // few slices, every pixel still belongs to a superpixel
+    let thin = Array4::from_shape_fn((2, 40, 40, 1), |(_, y, x, _)| (x / 20 + y / 20) as f64);
+    let labels: Array3<usize> = disf_superpixels(&thin.view(), 4, 50);
+    assert!(labels.iter().all(|&l| l >= 1));
+}
+
+macro_rules! impl_isf_superpixels {
+    ($new_name:ident, $dim:expr) => {
+        #[pyfunction]
+        pub fn $new_name<'py>(
+            py: Python<'py>,
+            image: PyReadonlyArray<f64, Dim<[usize; $dim + 1]>>,
+            n_superpixels: usize,
+            compactness: f64,
+            n_iterations: usize,
+        ) -> PyResult<&'py PyArray<usize, Dim<[usize; $dim]>>> {
+            let labels: Array<usize, Dim<[usize; $dim]>> =
+                isf_superpixels(&image.as_array(), n_superpixels, compactness, n_iterations);
+            Ok(labels.into_pyarray(py))
+        }
+    };
+}
+
+impl_isf_superpixels!(isf_superpixels_2d, 2);
+impl_isf_superpixels!(isf_superpixels_3d, 3);
+
+macro_rules! impl_disf_superpixels {
+    ($new_name:ident, $dim:expr) => {
+        #[pyfunction]
+        pub fn $new_name<'py>(
+            py: Python<'py>,
+            image: PyReadonlyArray<f64, Dim<[usize; $dim + 1]>>,
+            n_superpixels: usize,
+            n_initial_seeds: usize,
+        ) -> PyResult<&'py PyArray<usize, Dim<[usize; $dim]>>> {
+            let labels: Array<usize, Dim<[usize; $dim]>> =
+                disf_superpixels(&image.as_array(), n_superpixels, n_initial_seeds);
+            Ok(labels.into_pyarray(py))
+        }
+    };
+}
+
+impl_disf_superpixels!(disf_superpixels_2d, 2);
+impl_disf_superpixels!(disf_superpixels_3d, 3);
@@ -1,26 +1,76 @@
 use ndarray::*;
 use num_traits::{Bounded, ToPrimitive, Zero};
-use numpy::{Element, IntoPyArray, PyArray, PyReadonlyArray};
+use numpy::{Element, PyReadonlyArray};
 use pyo3::prelude::*;
-use std::any::TypeId;
 use std::fmt::Debug;
 use std::ops::{Add, Sub};
 
-use crate::adjacency::{Adjacency, AdjacencyGrid2D, AdjacencyGrid3D};
-use crate::bucket_queue::BucketQueue;
-use crate::heap::Heap;
-use crate::priority_queue::{ElemStatus, PriorityQueue};
+use crate::adjacency::{grid_adjacency, squared_distance};
+use crate::forest::{OptimumPathForest, PyForest};
+use crate::ift::{
+    image_foresting_transform, image_foresting_transform_with_line, FMax, FMin, PathCost, Seed,
+};
+use crate::priority_queue::Policy;
 
-fn is_float<T: 'static>() -> bool {
-    let type_id = TypeId::of::<T>();
-    type_id == TypeId::of::<f32>() || type_id == TypeId::of::<f64>()
+/// fmax where the `h` handicap of the trivial paths is removed once they root a tree.
+struct FMaxHMinima<'a, T> {
+    topology: ArrayView1<'a, T>,
 }
 
+impl<'a, T> PathCost<T> for FMaxHMinima<'a, T>
+where
+    T: Copy + PartialOrd,
+{
+    #[inline(always)]
+    fn extend(&self, _p: usize, q: usize, cost_p: T, _root: usize) -> T {
+        if self.topology[q] > cost_p {
+            self.topology[q]
+        } else {
+            cost_p
+        }
+    }
+
+    fn root_cost(&self, p: usize, _cost: T) -> T {
+        self.topology[p]
+    }
+}
+
+/// fmin where the `h` handicap of the trivial paths is removed once they root a tree.
+struct FMinHMaxima<'a, T> {
+    topology: ArrayView1<'a, T>,
+}
+
+impl<'a, T> PathCost<T> for FMinHMaxima<'a, T>
+where
+    T: Copy + PartialOrd,
+{
+    #[inline(always)]
+    fn extend(&self, _p: usize, q: usize, cost_p: T, _root: usize) -> T {
+        if self.topology[q] < cost_p {
+            self.topology[q]
+        } else {
+            cost_p
+        }
+    }
+
+    fn policy(&self) -> Policy {
+        Policy::Max
+    }
+
+    fn root_cost(&self, p: usize, _cost: T) -> T {
+        self.topology[p]
+    }
+}
+
+/// Watershed from the minima with dynamics above `h`, or from the maxima flooding the topology
+/// downwards when `from_maxima` is set.
 pub fn watershed_from_minima<T, D>(
     topology: &ArrayView<T, D>,
     mask: &ArrayView<bool, D>,
     h: T,
-) -> Array<usize, D>
+    watershed_line: bool,
+    from_maxima: bool,
+) -> OptimumPathForest<T, D>
 where
     T: Add<Output = T>
         + Sub<Output = T>
@@ -35,13 +85,8 @@ where
         + 'static,
     D: Dimension,
 {
-    let shape = topology.dim();
-
-    let adj: Box<dyn Adjacency> = match D::NDIM.unwrap() {
-        2 => Box::new(AdjacencyGrid2D::new(topology.shape())),
-        3 => Box::new(AdjacencyGrid3D::new(topology.shape())),
-        _ => panic!("Unsupported dimension!"),
-    };
+    let shape = topology.raw_dim();
+    let adj = grid_adjacency(topology.shape());
 
     let topology = topology.to_shape(topology.len()).unwrap();
     let mask = mask.to_shape(mask.len()).unwrap();
@@ -50,57 +95,76 @@ where
         panic!("h must be greater than 0");
     }
 
-    let size: usize = topology.len();
-    let mut root: Array1<usize> = Array1::from_shape_vec(Ix1(size), (0..size).collect()).unwrap();
-
-    let mut cost = topology.to_shape(size).unwrap().mapv(|x| x + h);
-
-    let mut queue: Box<dyn PriorityQueue<T>> = match is_float::<T>() {
-        false => Box::new(BucketQueue::new(&mut cost)),
-        true => Box::new(Heap::new(&mut cost)),
-        _ => panic!("Unsupported type!"),
-    };
-
-    for i in 0..size {
-        if mask[i] {
-            queue.insert(i, -1).unwrap();
+    // handicaps that do not fit in T saturate at the extreme value instead of wrapping around
+    let saturates = |x: T| {
+        if from_maxima {
+            !(x >= T::min_value() + h && x - h < x)
+        } else {
+            !(x <= T::max_value() - h && x + h > x)
         }
-    }
-
-    while !queue.is_empty() {
-        let p = queue.pop().unwrap();
+    };
+    let handicap = |x: T| match (saturates(x), from_maxima) {
+        (true, false) => T::max_value(),
+        (true, true) => T::min_value(),
+        (false, false) => x + h,
+        (false, true) => x - h,
+    };
+    let deeper = |x: T, y: T| if from_maxima { x > y } else { x < y };
 
-        if root[p] == p {
-            queue.update_value(p, topology[p], -1);
-        }
+    // avoiding zero label in segmentation label
+    let mut seeds: Vec<Seed<T>> = (0..topology.len())
+        .filter(|&i| mask[i] && !saturates(topology[i]))
+        .map(|i| Seed::new(i, handicap(topology[i]), i + 1))
+        .collect();
 
-        for q in adj.neighbors(p) {
-            if mask[q] && queue.get_status(q) != ElemStatus::POPPED {
-                // fmax
-                let path_cost: T = if topology[q] > queue.get_value(p) {
-                    topology[q]
-                } else {
-                    queue.get_value(p)
-                };
-
-                if path_cost < queue.get_value(q) {
-                    root[q] = root[p];
-                    queue.update_value(q, path_cost, p as i64);
+    // saturated trivial paths would tie with the real paths reaching them and root spurious trees,
+    // so they only seed the components where no other pixel does, from their deepest pixel
+    if (0..topology.len()).any(|i| mask[i] && saturates(topology[i])) {
+        let mut visited = vec![false; topology.len()];
+        for start in 0..topology.len() {
+            if !mask[start] || visited[start] {
+                continue;
+            }
+            visited[start] = true;
+            let mut stack = vec![start];
+            let mut deepest = start;
+            let mut has_seed = false;
+            while let Some(p) = stack.pop() {
+                has_seed |= !saturates(topology[p]);
+                if deeper(topology[p], topology[deepest])
+                    || (topology[p] == topology[deepest] && p < deepest)
+                {
+                    deepest = p;
                 }
+                for q in adj.neighbors(p) {
+                    if mask[q] && !visited[q] {
+                        visited[q] = true;
+                        stack.push(q);
+                    }
+                }
+            }
+            if !has_seed {
+                seeds.push(Seed::new(deepest, handicap(topology[deepest]), deepest + 1));
             }
         }
     }
 
-    // avoiding zero label in segmentation label
-    for i in 0..size {
-        if mask[i] {
-            root[i] += 1;
-        } else {
-            root[i] = 0;
-        }
-    }
+    let mut path_cost: Box<dyn PathCost<T>> = if from_maxima {
+        Box::new(FMinHMaxima {
+            topology: topology.view(),
+        })
+    } else {
+        Box::new(FMaxHMinima {
+            topology: topology.view(),
+        })
+    };
 
-    root.into_shape(shape).unwrap()
+    let forest = if watershed_line {
+        image_foresting_transform_with_line(adj.as_ref(), path_cost.as_mut(), &seeds, &mask.view())
+    } else {
+        image_foresting_transform(adj.as_ref(), path_cost.as_mut(), &seeds, &mask.view())
+    };
+    forest.into_shape(shape)
 }
 
 #[test]
@@ -113,8 +177,267 @@ fn test_watershed_from_minima() {
 
     let expected_labels = array![[1, 1, 1], [1, 1, 1], [1, 1, 1]];
 
-    let result = watershed_from_minima(&image.view(), &mask.view(), 1);
-    assert_eq!(result, expected_labels);
+    let result = watershed_from_minima(&image.view(), &mask.view(), 1, false, false);
+    assert_eq!(result.labels, expected_labels);
+    assert_eq!(result.costs, image);
+    assert_eq!(result.roots, Array2::zeros(shape));
+
+    // the single maximum floods the whole image, the handicap saturates at zero
+    let result = watershed_from_minima(&image.view(), &mask.view(), 2, false, true);
+    assert_eq!(result.labels, Array2::from_elem(shape, 9));
+    assert_eq!(result.costs, image);
+    assert_eq!(result.roots, Array2::from_elem(shape, 8));
+
+    // pixels at the extremes of the dtype join a basin instead of rooting their own
+    let image = array![[0u8, 255, 1, 3, 2, 9, 0]];
+    let mask = Array2::from_elem(image.dim(), true);
+    let result = watershed_from_minima(&image.view(), &mask.view(), 1, false, false);
+    assert!(result.labels.iter().all(|&l| l != 0));
+    assert_eq!(result.labels[[0, 1]], result.labels[[0, 0]]);
+    assert_eq!(result.preds[[0, 1]], 0);
+
+    let image = array![[0u8, 200, 0, 150, 0]];
+    let result = watershed_from_minima(&image.view(), &mask.slice(s![.., ..5]), 10, false, true);
+    assert_eq!(result.labels, array![[2, 2, 2, 4, 4]]);
+    assert_eq!(result.costs, image);
+
+    // a component where every handicap saturates is a single basin from its deepest pixel
+    let image = array![[255u8, 254, 255], [255, 255, 255]];
+    let mask = Array2::from_elem(image.dim(), true);
+    let result = watershed_from_minima(&image.view(), &mask.view(), 5, false, false);
+    assert_eq!(result.labels, Array2::from_elem(image.dim(), 2));
+    assert_eq!(result.costs, image);
+    assert_eq!(result.roots, Array2::from_elem(image.dim(), 1));
+}
+
+/// Watershed from the `markers` flooding the topology upwards, or downwards when `from_maxima` is set.
+pub fn watershed_from_markers<T, D>(
+    topology: &ArrayView<T, D>,
+    markers: &ArrayView<usize, D>,
+    mask: &ArrayView<bool, D>,
+    watershed_line: bool,
+    from_maxima: bool,
+) -> OptimumPathForest<T, D>
+where
+    T: Add<Output = T>
+        + Sub<Output = T>
+        + Bounded
+        + Copy
+        + Clone
+        + Debug
+        + Element
+        + PartialOrd
+        + ToPrimitive
+        + Zero
+        + 'static,
+    D: Dimension,
+{
+    let shape = topology.raw_dim();
+    let adj = grid_adjacency(topology.shape());
+
+    let topology = topology.to_shape(topology.len()).unwrap();
+    let markers = markers.to_shape(markers.len()).unwrap();
+    let mask = mask.to_shape(mask.len()).unwrap();
+
+    let seeds: Vec<Seed<T>> = (0..topology.len())
+        .filter(|&i| mask[i] && markers[i] != 0)
+        .map(|i| Seed::new(i, topology[i], markers[i]))
+        .collect();
+
+    let weight = |_, q: usize| topology[q];
+    let mut path_cost: Box<dyn PathCost<T>> = if from_maxima {
+        Box::new(FMin::new(weight))
+    } else {
+        Box::new(FMax::new(weight))
+    };
+
+    let forest = if watershed_line {
+        image_foresting_transform_with_line(adj.as_ref(), path_cost.as_mut(), &seeds, &mask.view())
+    } else {
+        image_foresting_transform(adj.as_ref(), path_cost.as_mut(), &seeds, &mask.view())
+    };
+    forest.into_shape(shape)
+}
+
+#[test]
+fn test_watershed_from_markers() {
+    let image = array![
+        [0, 1, 5, 1, 0],
+        [0, 1, 6, 2, 0],
+        [0, 1, 5, 1, 0],
+        [0, 3, 7, 1, 0],
+    ];
+    let shape = image.dim();
+    let mut mask = Array2::from_elem(shape, true);
+    mask[[3, 0]] = false;
+    mask.column_mut(2).fill(false);
+
+    let mut markers = Array2::<usize>::zeros(shape);
+    markers[[0, 0]] = 3;
+    markers[[3, 4]] = 7;
+
+    let expected_labels = array![
+        [3, 3, 0, 7, 7],
+        [3, 3, 0, 7, 7],
+        [3, 3, 0, 7, 7],
+        [0, 3, 0, 7, 7],
+    ];
+
+    let result = watershed_from_markers(&image.view(), &markers.view(), &mask.view(), false, false);
+    assert_eq!(result.labels, expected_labels);
+    assert_eq!(result.costs[[2, 1]], 1);
+    assert_eq!(result.costs[[3, 1]], 3);
+    assert_eq!(result.preds[[0, 0]], -1);
+    assert_eq!(result.roots[[3, 3]], 19);
+
+    let inverted = image.mapv(|x| 7 - x);
+    let result =
+        watershed_from_markers(&inverted.view(), &markers.view(), &mask.view(), false, true);
+    assert_eq!(result.labels, expected_labels);
+    assert_eq!(result.costs[[3, 1]], 4);
+
+    // from the maxima, plateaus at the smallest value of the dtype are conquered too
+    let image = array![[200u8, 0, 100], [200, 0, 100]];
+    let mut markers = Array2::<usize>::zeros(image.dim());
+    markers[[0, 0]] = 1;
+    markers[[1, 2]] = 2;
+    let mask = Array2::from_elem(image.dim(), true);
+    let result = watershed_from_markers(&image.view(), &markers.view(), &mask.view(), false, true);
+    assert_eq!(result.labels, array![[1, 1, 2], [1, 1, 2]]);
+    assert_eq!(result.costs, image);
+
+    // plateaus at the largest value of the dtype are conquered too
+    let image = array![[0u8, 255, 10], [0, 255, 10]];
+    let mut markers = Array2::<usize>::zeros(image.dim());
+    markers[[0, 0]] = 1;
+    markers[[1, 2]] = 2;
+    let mask = Array2::from_elem(image.dim(), true);
+    let result = watershed_from_markers(&image.view(), &markers.view(), &mask.view(), false, false);
+    assert_eq!(result.labels, array![[1, 1, 2], [1, 1, 2]]);
+    assert_eq!(result.costs, array![[0, 255, 10], [0, 255, 10]]);
+    assert!(result.roots.iter().all(|&r| r >= 0));
+
+    let image = image.mapv(|x| if x == 255 { f32::MAX } else { x as f32 });
+    let result = watershed_from_markers(&image.view(), &markers.view(), &mask.view(), false, false);
+    assert_eq!(result.labels, array![[1, 1, 2], [1, 1, 2]]);
+
+    // non-negative markers on a signed image whose costs decrease below zero
+    let image = array![[5i16, -3, -2, 4]];
+    let mut markers = Array2::<usize>::zeros(image.dim());
+    markers[[0, 0]] = 1;
+    markers[[0, 3]] = 2;
+    let mask = Array2::from_elem(image.dim(), true);
+    let result = watershed_from_markers(&image.view(), &markers.view(), &mask.view(), false, true);
+    assert_eq!(result.labels, array![[1, 1, 2, 2]]);
+    assert_eq!(result.costs, array![[5, -3, -2, 4]]);
+
+    // the line is one pixel thick whether the basins meet on a pixel or between two pixels
+    let image = array![[0, 1, 5, 1, 0], [0, 1, 5, 5, 0]];
+    let mut markers = Array2::<usize>::zeros(image.dim());
+    markers[[0, 0]] = 1;
+    markers[[0, 4]] = 2;
+    let mask = Array2::from_elem(image.dim(), true);
+
+    let result = watershed_from_markers(&image.view(), &markers.view(), &mask.view(), true, false);
+    assert_eq!(result.labels, array![[1, 1, 0, 2, 2], [1, 1, 0, 2, 2]]);
+    assert_eq!(result.preds.column(2), array![-1, -1]);
+    assert_eq!(result.roots.column(2), array![-1, -1]);
+
+    // lines are computed on the 6-neighborhood in 3D
+    let image = stack![Axis(0), image, image];
+    let mut markers = Array3::<usize>::zeros(image.dim());
+    markers[[0, 0, 0]] = 1;
+    markers[[0, 0, 4]] = 2;
+    let mask = Array3::from_elem(image.dim(), true);
+    let result = watershed_from_markers(&image.view(), &markers.view(), &mask.view(), true, false);
+    let expected = array![[1, 1, 0, 2, 2], [1, 1, 0, 2, 2]];
+    assert_eq!(result.labels, stack![Axis(0), expected, expected]);
+    assert!(result.roots.slice(s![.., .., 2]).iter().all(|&r| r == -1));
+}
+
+/// Cost of `q` in the compact watershed, its topology value plus `compactness` times its Euclidean
+/// distance to the seed of the tree reaching it, regardless of the cost of `p`.
+struct CompactCost<'a, T> {
+    topology: ArrayView1<'a, T>,
+    shape: Vec<usize>,
+    spacing: Vec<f64>,
+    compactness: f64,
+}
+
+impl<'a, T> PathCost<f64> for CompactCost<'a, T>
+where
+    T: Copy + ToPrimitive,
+{
+    #[inline(always)]
+    fn extend(&self, _p: usize, q: usize, _cost_p: f64, root: usize) -> f64 {
+        let distance = squared_distance(&self.shape, &self.spacing, q, root).sqrt();
+        self.topology[q].to_f64().unwrap() + self.compactness * distance
+    }
+}
+
+/// Watershed from markers where the pixels are flooded in the order of their topology value plus
+/// `compactness` times their distance to the marker pixel rooting their tree, as in scikit-image.
+/// Larger `compactness` gives more regular basins, zero gives the regular watershed.
+pub fn compact_watershed<T, D>(
+    topology: &ArrayView<T, D>,
+    markers: &ArrayView<usize, D>,
+    mask: &ArrayView<bool, D>,
+    compactness: f64,
+    watershed_line: bool,
+) -> OptimumPathForest<f64, D>
+where
+    T: Copy + ToPrimitive,
+    D: Dimension,
+{
+    if compactness < 0.0 {
+        panic!("compactness must be non-negative");
+    }
+
+    let shape = topology.raw_dim();
+    let adj = grid_adjacency(topology.shape());
+
+    let flat_topology = topology.to_shape(topology.len()).unwrap();
+    let mut path_cost = CompactCost {
+        topology: flat_topology.view(),
+        shape: topology.shape().to_vec(),
+        spacing: vec![1.0; topology.ndim()],
+        compactness,
+    };
+    let markers = markers.to_shape(markers.len()).unwrap();
+    let mask = mask.to_shape(mask.len()).unwrap();
+
+    let seeds: Vec<Seed<f64>> = (0..markers.len())
+        .filter(|&i| mask[i] && markers[i] != 0)
+        .map(|i| Seed::new(i, flat_topology[i].to_f64().unwrap(), markers[i]))
+        .collect();
+
+    let forest = if watershed_line {
+        image_foresting_transform_with_line(adj.as_ref(), &mut path_cost, &seeds, &mask.view())
+    } else {
+        image_foresting_transform(adj.as_ref(), &mut path_cost, &seeds, &mask.view())
+    };
+    forest.into_shape(shape)
+}
+
+#[test]
+fn test_compact_watershed() {
+    let image = array![[0, 0, 0, 0, 0, 0, 3, 0]];
+    let mut markers = Array2::<usize>::zeros(image.dim());
+    markers[[0, 0]] = 1;
+    markers[[0, 7]] = 2;
+    let mask = Array2::from_elem(image.dim(), true);
+
+    let regular =
+        watershed_from_markers(&image.view(), &markers.view(), &mask.view(), false, false);
+    assert_eq!(regular.labels, array![[1, 1, 1, 1, 1, 1, 2, 2]]);
+    let result = compact_watershed(&image.view(), &markers.view(), &mask.view(), 0.0, false);
+    assert_eq!(result.labels, regular.labels);
+
+    // the low plateau no longer lets the first basin leak up to the second marker
+    let result = compact_watershed(&image.view(), &markers.view(), &mask.view(), 10.0, false);
+    assert_eq!(result.labels, array![[1, 1, 1, 1, 2, 2, 2, 2]]);
+    assert_eq!(result.costs[[0, 6]], 13.0);
+    assert_eq!(result.roots[[0, 4]], 7);
 }
 
 macro_rules! impl_watershed_from_minima {
@@ -125,10 +448,17 @@ macro_rules! impl_watershed_from_minima {
             topology: PyReadonlyArray<$ty, Dim<[usize; $dim]>>,
             mask: PyReadonlyArray<bool, Dim<[usize; $dim]>>,
             h: $ty,
-        ) -> PyResult<&'py PyArray<usize, Dim<[usize; $dim]>>> {
-            let arr = watershed_from_minima(&topology.as_array(), &mask.as_array(), h);
-            let py_array = arr.into_pyarray(py);
-            Ok(py_array)
+            watershed_line: bool,
+            from_maxima: bool,
+        ) -> PyResult<PyForest<'py, $ty, Dim<[usize; $dim]>>> {
+            let forest = watershed_from_minima(
+                &topology.as_array(),
+                &mask.as_array(),
+                h,
+                watershed_line,
+                from_maxima,
+            );
+            Ok(forest.into_pyarrays(py))
         }
     };
 }
@@ -151,3 +481,87 @@ impl_watershed_from_minima!(watershed_from_minima_f32_2d, f32, 2);
 impl_watershed_from_minima!(watershed_from_minima_f32_3d, f32, 3);
 impl_watershed_from_minima!(watershed_from_minima_f64_2d, f64, 2);
 impl_watershed_from_minima!(watershed_from_minima_f64_3d, f64, 3);
+
+macro_rules! impl_watershed_from_markers {
+    ($new_name:ident, $ty:ty, $dim:expr) => {
+        #[pyfunction]
+        pub fn $new_name<'py>(
+            py: Python<'py>,
+            topology: PyReadonlyArray<$ty, Dim<[usize; $dim]>>,
+            markers: PyReadonlyArray<usize, Dim<[usize; $dim]>>,
+            mask: PyReadonlyArray<bool, Dim<[usize; $dim]>>,
+            watershed_line: bool,
+            from_maxima: bool,
+        ) -> PyResult<PyForest<'py, $ty, Dim<[usize; $dim]>>> {
+            let forest = watershed_from_markers(
+                &topology.as_array(),
+                &markers.as_array(),
+                &mask.as_array(),
+                watershed_line,
+                from_maxima,
+            );
+            Ok(forest.into_pyarrays(py))
+        }
+    };
+}
+
+impl_watershed_from_markers!(watershed_from_markers_u8_2d, u8, 2);
+impl_watershed_from_markers!(watershed_from_markers_u8_3d, u8, 3);
+impl_watershed_from_markers!(watershed_from_markers_u16_2d, u16, 2);
+impl_watershed_from_markers!(watershed_from_markers_u16_3d, u16, 3);
+impl_watershed_from_markers!(watershed_from_markers_u32_2d, u32, 2);
+impl_watershed_from_markers!(watershed_from_markers_u32_3d, u32, 3);
+
+impl_watershed_from_markers!(watershed_from_markers_i16_2d, i16, 2);
+impl_watershed_from_markers!(watershed_from_markers_i16_3d, i16, 3);
+impl_watershed_from_markers!(watershed_from_markers_i32_2d, i32, 2);
+impl_watershed_from_markers!(watershed_from_markers_i32_3d, i32, 3);
+impl_watershed_from_markers!(watershed_from_markers_i64_2d, i64, 2);
+impl_watershed_from_markers!(watershed_from_markers_i64_3d, i64, 3);
+
+impl_watershed_from_markers!(watershed_from_markers_f32_2d, f32, 2);
+impl_watershed_from_markers!(watershed_from_markers_f32_3d, f32, 3);
+impl_watershed_from_markers!(watershed_from_markers_f64_2d, f64, 2);
+impl_watershed_from_markers!(watershed_from_markers_f64_3d, f64, 3);
+
+macro_rules! impl_compact_watershed {
+    ($new_name:ident, $ty:ty, $dim:expr) => {
+        #[pyfunction]
+        pub fn $new_name<'py>(
+            py: Python<'py>,
+            topology: PyReadonlyArray<$ty, Dim<[usize; $dim]>>,
+            markers: PyReadonlyArray<usize, Dim<[usize; $dim]>>,
+            mask: PyReadonlyArray<bool, Dim<[usize; $dim]>>,
+            compactness: f64,
+            watershed_line: bool,
+        ) -> PyResult<PyForest<'py, f64, Dim<[usize; $dim]>>> {
+            let forest = compact_watershed(
+                &topology.as_array(),
+                &markers.as_array(),
+                &mask.as_array(),
+                compactness,
+                watershed_line,
+            );
+            Ok(forest.into_pyarrays(py))
+        }
+    };
+}
+
+impl_compact_watershed!(compact_watershed_u8_2d, u8, 2);
+impl_compact_watershed!(compact_watershed_u8_3d, u8, 3);
+impl_compact_watershed!(compact_watershed_u16_2d, u16, 2);
+impl_compact_watershed!(compact_watershed_u16_3d, u16, 3);
+impl_compact_watershed!(compact_watershed_u32_2d, u32, 2);
+impl_compact_watershed!(compact_watershed_u32_3d, u32, 3);
+
+impl_compact_watershed!(compact_watershed_i16_2d, i16, 2);
+impl_compact_watershed!(compact_watershed_i16_3d, i16, 3);
+impl_compact_watershed!(compact_watershed_i32_2d, i32, 2);
+impl_compact_watershed!(compact_watershed_i32_3d, i32, 3);
+impl_compact_watershed!(compact_watershed_i64_2d, i64, 2);
+impl_compact_watershed!(compact_watershed_i64_3d, i64, 3);
+
+impl_compact_watershed!(compact_watershed_f32_2d, f32, 2);
+impl_compact_watershed!(compact_watershed_f32_3d, f32, 3);
+impl_compact_watershed!(compact_watershed_f64_2d, f64, 2);
+impl_compact_watershed!(compact_watershed_f64_3d, f64, 3);